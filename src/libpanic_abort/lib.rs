@@ -126,12 +126,14 @@ pub mod personalities {
     #[cfg(all(target_os = "windows", target_env = "gnu"))]
     pub extern "C" fn rust_eh_unwind_resume() {}
 
-    // These two are called by our startup objects on i686-pc-windows-gnu, but
-    // they don't need to do anything so the bodies are nops.
+    // These two are called by our startup objects on i686-pc-windows-gnu and
+    // rvl-ios, but they don't need to do anything so the bodies are nops.
     #[no_mangle]
-    #[cfg(all(target_os = "windows", target_env = "gnu", target_arch = "x86"))]
+    #[cfg(any(all(target_os = "windows", target_env = "gnu", target_arch = "x86"),
+              target_os = "rvl-ios"))]
     pub extern "C" fn rust_eh_register_frames() {}
     #[no_mangle]
-    #[cfg(all(target_os = "windows", target_env = "gnu", target_arch = "x86"))]
+    #[cfg(any(all(target_os = "windows", target_env = "gnu", target_arch = "x86"),
+              target_os = "rvl-ios"))]
     pub extern "C" fn rust_eh_unregister_frames() {}
 }
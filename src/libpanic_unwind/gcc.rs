@@ -297,7 +297,11 @@ unsafe extern "C" fn rust_eh_unwind_resume(panic_ctx: *mut u8) -> ! {
 // implementation of stack unwinding is (for now) deferred to libgcc_eh, however
 // Rust crates use these Rust-specific entry points to avoid potential clashes
 // with any GCC runtime.
-#[cfg(all(target_os="windows", target_arch = "x86", target_env="gnu"))]
+//
+// rvl-ios is linked statically against libgcc_eh with no eh_frame_hdr to look
+// frames up by, so it relies on the same registration.
+#[cfg(any(all(target_os="windows", target_arch = "x86", target_env="gnu"),
+          target_os = "rvl-ios"))]
 pub mod eh_frame_registry {
     extern "C" {
         fn __register_frame_info(eh_frame_begin: *const u8, object: *mut u8);
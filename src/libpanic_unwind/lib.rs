@@ -58,7 +58,8 @@ cfg_if! {
         mod imp;
     } else {
         // Rust runtime's startup objects depend on these symbols, so make them public.
-        #[cfg(all(target_os="windows", target_arch = "x86", target_env="gnu"))]
+        #[cfg(any(all(target_os="windows", target_arch = "x86", target_env="gnu"),
+                  target_os = "rvl-ios"))]
         pub use imp::eh_frame_registry::*;
         #[path = "gcc.rs"]
         mod imp;
@@ -45,10 +45,10 @@ use crate::sys::os as os_imp;
 ///     Ok(())
 /// }
 /// ```
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn current_dir() -> io::Result<PathBuf> {
-//     os_imp::getcwd()
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn current_dir() -> io::Result<PathBuf> {
+    os_imp::getcwd()
+}
 
 /// Changes the current working directory to the specified path.
 ///
@@ -66,10 +66,10 @@ use crate::sys::os as os_imp;
 /// assert!(env::set_current_dir(&root).is_ok());
 /// println!("Successfully changed working directory to {}!", root.display());
 /// ```
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn set_current_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
-//     os_imp::chdir(path.as_ref())
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn set_current_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    os_imp::chdir(path.as_ref())
+}
 
 /// An iterator over a snapshot of the environment variables of this process.
 ///
@@ -77,8 +77,8 @@ use crate::sys::os as os_imp;
 /// documentation for more.
 ///
 /// [`std::env::vars`]: fn.vars.html
-// #[stable(feature = "env", since = "1.0.0")]
-// pub struct Vars { inner: VarsOs }
+#[stable(feature = "env", since = "1.0.0")]
+pub struct Vars { inner: VarsOs }
 
 /// An iterator over a snapshot of the environment variables of this process.
 ///
@@ -86,8 +86,8 @@ use crate::sys::os as os_imp;
 /// its documentation for more.
 ///
 /// [`std::env::vars_os`]: fn.vars_os.html
-// #[stable(feature = "env", since = "1.0.0")]
-// pub struct VarsOs { inner: os_imp::Env }
+#[stable(feature = "env", since = "1.0.0")]
+pub struct VarsOs { inner: os_imp::Env }
 
 /// Returns an iterator of (variable, value) pairs of strings, for all the
 /// environment variables of the current process.
@@ -115,10 +115,10 @@ use crate::sys::os as os_imp;
 ///     println!("{}: {}", key, value);
 /// }
 /// ```
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn vars() -> Vars {
-//     Vars { inner: vars_os() }
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn vars() -> Vars {
+    Vars { inner: vars_os() }
+}
 
 /// Returns an iterator of (variable, value) pairs of OS strings, for all the
 /// environment variables of the current process.
@@ -138,42 +138,42 @@ use crate::sys::os as os_imp;
 ///     println!("{:?}: {:?}", key, value);
 /// }
 /// ```
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn vars_os() -> VarsOs {
-//     VarsOs { inner: os_imp::env() }
-// }
-
-// #[stable(feature = "env", since = "1.0.0")]
-// impl Iterator for Vars {
-//     type Item = (String, String);
-//     fn next(&mut self) -> Option<(String, String)> {
-//         self.inner.next().map(|(a, b)| {
-//             (a.into_string().unwrap(), b.into_string().unwrap())
-//         })
-//     }
-//     fn size_hint(&self) -> (usize, Option<usize>) { self.inner.size_hint() }
-// }
-
-// #[stable(feature = "std_debug", since = "1.16.0")]
-// impl fmt::Debug for Vars {
-//     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-//         f.pad("Vars { .. }")
-//     }
-// }
-
-// #[stable(feature = "env", since = "1.0.0")]
-// impl Iterator for VarsOs {
-//     type Item = (OsString, OsString);
-//     fn next(&mut self) -> Option<(OsString, OsString)> { self.inner.next() }
-//     fn size_hint(&self) -> (usize, Option<usize>) { self.inner.size_hint() }
-// }
-
-// #[stable(feature = "std_debug", since = "1.16.0")]
-// impl fmt::Debug for VarsOs {
-//     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-//         f.pad("VarsOs { .. }")
-//     }
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn vars_os() -> VarsOs {
+    VarsOs { inner: os_imp::env() }
+}
+
+#[stable(feature = "env", since = "1.0.0")]
+impl Iterator for Vars {
+    type Item = (String, String);
+    fn next(&mut self) -> Option<(String, String)> {
+        self.inner.next().map(|(a, b)| {
+            (a.into_string().unwrap(), b.into_string().unwrap())
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) { self.inner.size_hint() }
+}
+
+#[stable(feature = "std_debug", since = "1.16.0")]
+impl fmt::Debug for Vars {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Vars { .. }")
+    }
+}
+
+#[stable(feature = "env", since = "1.0.0")]
+impl Iterator for VarsOs {
+    type Item = (OsString, OsString);
+    fn next(&mut self) -> Option<(OsString, OsString)> { self.inner.next() }
+    fn size_hint(&self) -> (usize, Option<usize>) { self.inner.size_hint() }
+}
+
+#[stable(feature = "std_debug", since = "1.16.0")]
+impl fmt::Debug for VarsOs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("VarsOs { .. }")
+    }
+}
 
 /// Fetches the environment variable `key` from the current process.
 ///
@@ -193,17 +193,17 @@ use crate::sys::os as os_imp;
 ///     Err(e) => println!("couldn't interpret {}: {}", key, e),
 /// }
 /// ```
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn var<K: AsRef<OsStr>>(key: K) -> Result<String, VarError> {
-//     _var(key.as_ref())
-// }
-
-// fn _var(key: &OsStr) -> Result<String, VarError> {
-//     match var_os(key) {
-//         Some(s) => s.into_string().map_err(VarError::NotUnicode),
-//         None => Err(VarError::NotPresent),
-//     }
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn var<K: AsRef<OsStr>>(key: K) -> Result<String, VarError> {
+    _var(key.as_ref())
+}
+
+fn _var(key: &OsStr) -> Result<String, VarError> {
+    match var_os(key) {
+        Some(s) => s.into_string().map_err(VarError::NotUnicode),
+        None => Err(VarError::NotPresent),
+    }
+}
 
 /// Fetches the environment variable `key` from the current process, returning
 /// [`None`] if the variable isn't set.
@@ -221,16 +221,16 @@ use crate::sys::os as os_imp;
 ///     None => println!("{} is not defined in the environment.", key)
 /// }
 /// ```
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn var_os<K: AsRef<OsStr>>(key: K) -> Option<OsString> {
-//     _var_os(key.as_ref())
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn var_os<K: AsRef<OsStr>>(key: K) -> Option<OsString> {
+    _var_os(key.as_ref())
+}
 
-// fn _var_os(key: &OsStr) -> Option<OsString> {
-//     os_imp::getenv(key).unwrap_or_else(|e| {
-//         panic!("failed to get environment variable `{:?}`: {}", key, e)
-//     })
-// }
+fn _var_os(key: &OsStr) -> Option<OsString> {
+    os_imp::getenv(key).unwrap_or_else(|e| {
+        panic!("failed to get environment variable `{:?}`: {}", key, e)
+    })
+}
 
 /// The error type for operations interacting with environment variables.
 /// Possibly returned from the [`env::var`] function.
@@ -392,10 +392,10 @@ pub struct SplitPaths<'a> {
 /// ```
 ///
 /// [`PathBuf`]: ../../std/path/struct.PathBuf.html
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn split_paths<T: AsRef<OsStr> + ?Sized>(unparsed: &T) -> SplitPaths<'_> {
-//     SplitPaths { inner: os_imp::split_paths(unparsed.as_ref()) }
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn split_paths<T: AsRef<OsStr> + ?Sized>(unparsed: &T) -> SplitPaths<'_> {
+    SplitPaths { inner: os_imp::split_paths(unparsed.as_ref()) }
+}
 
 #[stable(feature = "env", since = "1.0.0")]
 impl<'a> Iterator for SplitPaths<'a> {
@@ -432,7 +432,8 @@ pub struct JoinPathsError {
 ///
 /// Returns an [`Err`][err] (containing an error message) if one of the input
 /// [`Path`]s contains an invalid character for constructing the `PATH`
-/// variable (a double quote on Windows or a colon on Unix).
+/// variable (a double quote on Windows, a semicolon on rvl-ios or a colon on
+/// other Unix platforms).
 ///
 /// [`Path`]: ../../std/path/struct.Path.html
 /// [`OsString`]: ../../std/ffi/struct.OsString.html
@@ -448,7 +449,7 @@ pub struct JoinPathsError {
 /// use std::path::Path;
 ///
 /// fn main() -> Result<(), env::JoinPathsError> {
-/// # if cfg!(unix) {
+/// # if cfg!(all(unix, not(target_os = "rvl-ios"))) {
 ///     let paths = [Path::new("/bin"), Path::new("/usr/bin")];
 ///     let path_os_string = env::join_paths(paths.iter())?;
 ///     assert_eq!(path_os_string, OsString::from("/bin:/usr/bin"));
@@ -460,7 +461,7 @@ pub struct JoinPathsError {
 /// Joining a path containing a colon on a Unix-like platform results in an error:
 ///
 /// ```
-/// # if cfg!(unix) {
+/// # if cfg!(all(unix, not(target_os = "rvl-ios"))) {
 /// use std::env;
 /// use std::path::Path;
 ///
@@ -489,14 +490,14 @@ pub struct JoinPathsError {
 /// ```
 ///
 /// [`env::split_paths`]: fn.split_paths.html
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn join_paths<I, T>(paths: I) -> Result<OsString, JoinPathsError>
-//     where I: IntoIterator<Item=T>, T: AsRef<OsStr>
-// {
-//     os_imp::join_paths(paths.into_iter()).map_err(|e| {
-//         JoinPathsError { inner: e }
-//     })
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn join_paths<I, T>(paths: I) -> Result<OsString, JoinPathsError>
+    where I: IntoIterator<Item=T>, T: AsRef<OsStr>
+{
+    os_imp::join_paths(paths.into_iter()).map_err(|e| {
+        JoinPathsError { inner: e }
+    })
+}
 
 #[stable(feature = "env", since = "1.0.0")]
 impl fmt::Display for JoinPathsError {
@@ -543,13 +544,13 @@ impl Error for JoinPathsError {
 ///     None => println!("Impossible to get your home dir!"),
 /// }
 /// ```
-// #[rustc_deprecated(since = "1.29.0",
-//     reason = "This function's behavior is unexpected and probably not what you want. \
-//               Consider using the home_dir function from https://crates.io/crates/dirs instead.")]
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn home_dir() -> Option<PathBuf> {
-//     os_imp::home_dir()
-// }
+#[rustc_deprecated(since = "1.29.0",
+    reason = "This function's behavior is unexpected and probably not what you want. \
+              Consider using the home_dir function from https://crates.io/crates/dirs instead.")]
+#[stable(feature = "env", since = "1.0.0")]
+pub fn home_dir() -> Option<PathBuf> {
+    os_imp::home_dir()
+}
 
 /// Returns the path of a temporary directory.
 ///
@@ -582,10 +583,10 @@ impl Error for JoinPathsError {
 ///     Ok(())
 /// }
 /// ```
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn temp_dir() -> PathBuf {
-//     os_imp::temp_dir()
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn temp_dir() -> PathBuf {
+    os_imp::temp_dir()
+}
 
 /// Returns the full filesystem path of the current running executable.
 ///
@@ -718,10 +719,10 @@ pub struct ArgsOs {
 /// ```
 ///
 /// [`args_os`]: ./fn.args_os.html
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn args() -> Args {
-//     Args { inner: args_os() }
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn args() -> Args {
+    Args { inner: args_os() }
+}
 
 /// Returns the arguments which this program was started with (normally passed
 /// via the command line).
@@ -740,10 +741,10 @@ pub struct ArgsOs {
 ///     println!("{:?}", argument);
 /// }
 /// ```
-// #[stable(feature = "env", since = "1.0.0")]
-// pub fn args_os() -> ArgsOs {
-//     ArgsOs { inner: sys::args::args() }
-// }
+#[stable(feature = "env", since = "1.0.0")]
+pub fn args_os() -> ArgsOs {
+    ArgsOs { inner: sys::args::args() }
+}
 
 #[stable(feature = "env_unimpl_send_sync", since = "1.26.0")]
 impl !Send for Args {}
@@ -1046,7 +1047,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(unix)]
+    #[cfg(all(unix, not(target_os = "rvl-ios")))]
     fn split_paths_unix() {
         use crate::path::PathBuf;
 
@@ -1063,7 +1064,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(unix)]
+    #[cfg(all(unix, not(target_os = "rvl-ios")))]
     fn join_paths_unix() {
         use crate::ffi::OsStr;
 
@@ -1077,6 +1078,37 @@ mod tests {
         assert!(join_paths(["/te:st"].iter().cloned()).is_err());
     }
 
+    #[test]
+    #[cfg(target_os = "rvl-ios")]
+    fn split_paths_rvl_ios() {
+        use crate::path::PathBuf;
+
+        fn check_parse(unparsed: &str, parsed: &[&str]) -> bool {
+            split_paths(unparsed).collect::<Vec<_>>()
+                == parsed.iter().map(|s| PathBuf::from(*s)).collect::<Vec<_>>()
+        }
+
+        assert!(check_parse("", &mut [""]));
+        assert!(check_parse(";;", &mut ["", "", ""]));
+        assert!(check_parse("sd:/", &mut ["sd:/"]));
+        assert!(check_parse("sd:/apps;usb:/apps", &mut ["sd:/apps", "usb:/apps"]));
+    }
+
+    #[test]
+    #[cfg(target_os = "rvl-ios")]
+    fn join_paths_rvl_ios() {
+        use crate::ffi::OsStr;
+
+        fn test_eq(input: &[&str], output: &str) -> bool {
+            &*join_paths(input.iter().cloned()).unwrap() == OsStr::new(output)
+        }
+
+        assert!(test_eq(&[], ""));
+        assert!(test_eq(&["sd:/apps", "usb:/apps"], "sd:/apps;usb:/apps"));
+        assert!(test_eq(&["", "sd:/", "", ""], ";sd:/;;"));
+        assert!(join_paths(["sd:/te;st"].iter().cloned()).is_err());
+    }
+
     #[test]
     #[cfg(windows)]
     fn join_paths_windows() {
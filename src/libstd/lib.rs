@@ -267,6 +267,7 @@
 #![feature(fn_traits)]
 //#![feature(fnbox)]
 #![feature(generator_trait)]
+#![feature(global_asm)]
 #![feature(hash_raw_entry)]
 #![feature(hashmap_internals)]
 #![feature(int_error_internals)]
@@ -455,14 +456,14 @@ pub mod collections;
 pub mod env;
 pub mod error;
 pub mod ffi;
-//pub mod fs;
+pub mod fs;
 pub mod io;
 pub mod net;
 pub mod num;
 pub mod os;
 pub mod panic;
 pub mod path;
-//pub mod process;
+pub mod process;
 pub mod sync;
 pub mod time;
 
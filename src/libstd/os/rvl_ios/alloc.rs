@@ -0,0 +1,169 @@
+//! Allocators for a specific memory bank on rvl-ios.
+//!
+//! The Wii has 24 MB of fast MEM1 and 64 MB of MEM2. The [`System`]
+//! allocator uses newlib's `malloc`, which fills MEM1 first and only then
+//! moves on to MEM2, so there's no telling where a given allocation ends up.
+//! [`Mem1Alloc`] and [`Mem2Alloc`] always allocate from their bank. They can
+//! be used as the `#[global_allocator]` or for individual collections through
+//! the `Alloc` trait.
+//!
+//! Each of them takes memory from the top of its bank's arena whenever an
+//! allocation doesn't fit into what it already has, at least 64 KiB at a
+//! time, and leaves the rest of the arena to `malloc`. Call `reserve` before
+//! the first allocation to set up a fixed region instead. Every allocation is
+//! aligned to and padded out to a 32 byte cache line.
+//!
+//! [`System`]: ../../../alloc/struct.System.html
+//! [`Mem1Alloc`]: struct.Mem1Alloc.html
+//! [`Mem2Alloc`]: struct.Mem2Alloc.html
+
+#![unstable(feature = "rvl_ios_alloc", issue = "0")]
+
+use crate::alloc::{Alloc, AllocErr, GlobalAlloc, Layout};
+use crate::io;
+use crate::ptr::NonNull;
+use crate::sys::arena;
+
+/// Usage statistics of a memory bank allocator, as returned by
+/// `Mem1Alloc::stats` and `Mem2Alloc::stats`.
+#[derive(Copy, Clone, Debug)]
+pub struct ArenaStats(arena::Stats);
+
+impl ArenaStats {
+    /// Returns the number of bytes the allocator manages in total.
+    ///
+    /// This is zero until the allocator first allocates, and grows as it
+    /// takes more memory from the bank's arena.
+    pub fn size(&self) -> usize {
+        self.0.size
+    }
+
+    /// Returns the number of bytes not currently allocated.
+    pub fn free_bytes(&self) -> usize {
+        self.0.free
+    }
+
+    /// Returns the size of the largest contiguous free block. Larger
+    /// allocations take more memory from the bank's arena first, unless the
+    /// allocator was set up with `reserve`.
+    pub fn largest_free_block(&self) -> usize {
+        self.0.largest_free
+    }
+
+    /// Returns the largest number of bytes that were allocated at the same
+    /// time so far.
+    pub fn high_water_mark(&self) -> usize {
+        self.0.high_water
+    }
+}
+
+macro_rules! bank_alloc {
+    ($(#[$attr:meta])* $name:ident, $arena:expr) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, Default, Debug)]
+        pub struct $name;
+
+        impl $name {
+            /// Sets the allocator up with a fixed region of `size` bytes,
+            /// rounded up to a cache line, instead of taking memory from the
+            /// arena as it goes.
+            ///
+            /// # Errors
+            ///
+            /// Fails if the allocator already allocated or was reserved
+            /// before, or if the arena doesn't have `size` bytes left.
+            pub fn reserve(size: usize) -> io::Result<()> {
+                $arena.claim(size)
+            }
+
+            /// Returns the current usage statistics of the allocator.
+            pub fn stats() -> ArenaStats {
+                ArenaStats($arena.stats())
+            }
+        }
+
+        unsafe impl GlobalAlloc for $name {
+            #[inline]
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                $arena.alloc(layout)
+            }
+
+            #[inline]
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                $arena.dealloc(ptr, layout)
+            }
+        }
+
+        unsafe impl Alloc for $name {
+            #[inline]
+            unsafe fn alloc(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+                NonNull::new(GlobalAlloc::alloc(self, layout)).ok_or(AllocErr)
+            }
+
+            #[inline]
+            unsafe fn alloc_zeroed(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+                NonNull::new(GlobalAlloc::alloc_zeroed(self, layout)).ok_or(AllocErr)
+            }
+
+            #[inline]
+            unsafe fn dealloc(&mut self, ptr: NonNull<u8>, layout: Layout) {
+                GlobalAlloc::dealloc(self, ptr.as_ptr(), layout)
+            }
+
+            #[inline]
+            unsafe fn realloc(
+                &mut self,
+                ptr: NonNull<u8>,
+                layout: Layout,
+                new_size: usize,
+            ) -> Result<NonNull<u8>, AllocErr> {
+                NonNull::new(GlobalAlloc::realloc(self, ptr.as_ptr(), layout, new_size))
+                    .ok_or(AllocErr)
+            }
+        }
+    }
+}
+
+bank_alloc! {
+    /// An allocator that only hands out memory from MEM1.
+    ///
+    /// MEM1 is the 24 MB of 1T-SRAM the CPU and GPU reach fastest.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(rvl_ios_alloc)]
+    /// use std::os::rvl_ios::alloc::Mem1Alloc;
+    ///
+    /// #[global_allocator]
+    /// static GLOBAL: Mem1Alloc = Mem1Alloc;
+    ///
+    /// fn main() {
+    ///     let _v = vec![0u8; 4096];
+    ///     println!("{} bytes left", Mem1Alloc::stats().free_bytes());
+    /// }
+    /// ```
+    Mem1Alloc, arena::MEM1
+}
+
+bank_alloc! {
+    /// An allocator that only hands out memory from MEM2.
+    ///
+    /// MEM2 is the 64 MB of GDDR3, slower than MEM1 but with plenty of
+    /// room for large assets.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(rvl_ios_alloc, allocator_api)]
+    /// use std::alloc::{Alloc, Layout};
+    /// use std::os::rvl_ios::alloc::Mem2Alloc;
+    ///
+    /// Mem2Alloc::reserve(16 * 1024 * 1024).unwrap();
+    /// let layout = Layout::from_size_align(64 * 1024, 32).unwrap();
+    /// let buf = unsafe { Mem2Alloc.alloc(layout).unwrap() };
+    /// // ...
+    /// unsafe { Mem2Alloc.dealloc(buf, layout) };
+    /// ```
+    Mem2Alloc, arena::MEM2
+}
@@ -0,0 +1,209 @@
+//! Buffers that can be shared with hardware on rvl-ios.
+//!
+//! IOS IPC, the DVD drive, EXI devices and GX all access memory directly,
+//! bypassing the CPU's data cache. Buffers handed to them have to start on a
+//! 32 byte cache line, must not share their last cache line with unrelated
+//! data, and have to be flushed before the device reads them and invalidated
+//! before the CPU reads what the device wrote. [`DmaBuf`] takes care of the
+//! first two and offers [`flush`] and [`invalidate`] for the rest.
+//!
+//! [`DmaBuf`]: struct.DmaBuf.html
+//! [`flush`]: struct.DmaBuf.html#method.flush
+//! [`invalidate`]: struct.DmaBuf.html#method.invalidate
+
+#![unstable(feature = "rvl_ios_dma", issue = "0")]
+
+use crate::alloc::{self, GlobalAlloc, Layout, System};
+use crate::cmp;
+use crate::fmt;
+use crate::io::{self, Read, Write};
+use crate::marker::PhantomData;
+use crate::mem;
+use crate::ops::{Deref, DerefMut};
+use crate::ptr::{self, NonNull};
+use crate::slice;
+
+/// The cache line size of Broadway, and the alignment DMA requires.
+pub const CACHE_LINE: usize = 32;
+
+/// A fixed-size, cache line aligned heap buffer of `T`s for use with DMA.
+///
+/// The buffer is allocated with the system allocator and padded out to a
+/// whole number of cache lines, so flushing or invalidating it never touches
+/// other data. It dereferences to a slice of its elements.
+///
+/// A `DmaBuf<u8>` additionally keeps a position, like [`io::Cursor`], and
+/// implements [`Read`] and [`Write`] on top of it.
+///
+/// [`io::Cursor`]: ../../../io/struct.Cursor.html
+/// [`Read`]: ../../../io/trait.Read.html
+/// [`Write`]: ../../../io/trait.Write.html
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(rvl_ios_dma)]
+/// use std::io::Write;
+/// use std::os::rvl_ios::dma::DmaBuf;
+///
+/// let mut buf: DmaBuf = DmaBuf::new(64);
+/// buf.write_all(b"request").unwrap();
+/// buf.flush();
+/// // hand `buf.as_ptr()` to IOS ...
+/// unsafe { buf.invalidate() };
+/// let reply = &buf[..];
+/// ```
+pub struct DmaBuf<T: Copy = u8> {
+    ptr: NonNull<T>,
+    len: usize,
+    pos: usize,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for DmaBuf<T> {}
+unsafe impl<T: Copy + Sync> Sync for DmaBuf<T> {}
+
+impl<T: Copy + Default> DmaBuf<T> {
+    /// Allocates a buffer of `len` elements, all set to `T::default()`.
+    pub fn new(len: usize) -> DmaBuf<T> {
+        DmaBuf::from_elem(T::default(), len)
+    }
+}
+
+impl<T: Copy> DmaBuf<T> {
+    /// Allocates a buffer of `len` copies of `elem`.
+    pub fn from_elem(elem: T, len: usize) -> DmaBuf<T> {
+        let buf = DmaBuf::alloc(len);
+        unsafe {
+            for i in 0..len {
+                ptr::write(buf.ptr.as_ptr().add(i), elem);
+            }
+        }
+        buf
+    }
+
+    /// Allocates a buffer holding a copy of `elems`.
+    pub fn from_slice(elems: &[T]) -> DmaBuf<T> {
+        let buf = DmaBuf::alloc(elems.len());
+        unsafe {
+            ptr::copy_nonoverlapping(elems.as_ptr(), buf.ptr.as_ptr(), elems.len());
+        }
+        buf
+    }
+
+    fn layout(len: usize) -> Layout {
+        let align = cmp::max(CACHE_LINE, mem::align_of::<T>());
+        len.checked_mul(mem::size_of::<T>())
+            .and_then(|size| size.checked_add(align - 1))
+            .and_then(|size| Layout::from_size_align(cmp::max(size & !(align - 1), align), align)
+                .ok())
+            .expect("capacity overflow")
+    }
+
+    fn alloc(len: usize) -> DmaBuf<T> {
+        let layout = DmaBuf::<T>::layout(len);
+        let ptr = unsafe { GlobalAlloc::alloc(&System, layout) };
+        let ptr = match NonNull::new(ptr as *mut T) {
+            Some(ptr) => ptr,
+            None => alloc::handle_alloc_error(layout),
+        };
+        DmaBuf { ptr, len, pos: 0, _marker: PhantomData }
+    }
+
+    /// Returns the number of bytes the buffer covers in memory, including
+    /// the padding up to the end of its last cache line.
+    pub fn padded_len(&self) -> usize {
+        DmaBuf::<T>::layout(self.len).size()
+    }
+
+    /// Writes the buffer back from the data cache to memory.
+    ///
+    /// Call this after filling the buffer and before a device reads it. For a
+    /// `DmaBuf<u8>`, `Write::flush` does the same.
+    pub fn flush(&self) {
+        unsafe {
+            ogc_sys::DCFlushRange(self.ptr.as_ptr() as *mut _, self.padded_len() as u32);
+        }
+    }
+
+    /// Discards the buffer's lines in the data cache, so the next reads see
+    /// what is in memory.
+    ///
+    /// Call this after a device wrote to the buffer and before reading it.
+    /// Writes made through the cache that weren't flushed yet are lost.
+    ///
+    /// # Safety
+    ///
+    /// Since the last flush, devices must only have written valid values of
+    /// `T` to the buffer. For plain data like integers any contents are fine.
+    pub unsafe fn invalidate(&mut self) {
+        ogc_sys::DCInvalidateRange(self.ptr.as_ptr() as *mut _, self.padded_len() as u32);
+    }
+}
+
+impl DmaBuf<u8> {
+    /// Returns the current position of the cursor used by `Read` and `Write`.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Moves the cursor used by `Read` and `Write` to `pos`.
+    pub fn set_position(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    fn remaining(&mut self) -> &mut [u8] {
+        let pos = cmp::min(self.pos, self.len);
+        &mut self[pos..]
+    }
+}
+
+impl<T: Copy> Deref for DmaBuf<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T: Copy> DerefMut for DmaBuf<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T: Copy> Drop for DmaBuf<T> {
+    fn drop(&mut self) {
+        unsafe {
+            GlobalAlloc::dealloc(&System, self.ptr.as_ptr() as *mut u8,
+                                 DmaBuf::<T>::layout(self.len));
+        }
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for DmaBuf<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl Read for DmaBuf<u8> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = Read::read(&mut &*self.remaining(), buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Write for DmaBuf<u8> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = Write::write(&mut self.remaining(), buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        DmaBuf::flush(self);
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+//! Turning CPU exceptions into panics on rvl-ios.
+//!
+//! libogc's own exception handler dumps the registers to the screen and
+//! halts the console, so neither panic hooks nor anything else in the
+//! program gets to know about a crash. The standard library replaces it at
+//! startup for the exceptions a program can cause by itself: a faulting
+//! load, store or instruction fetch, a misaligned access, an illegal or
+//! privileged instruction, and an enabled floating point exception.
+//!
+//! When one of these occurs, the faulting thread continues in the current
+//! exception handler (see [`set_handler`]) instead of at the faulting
+//! instruction, which is never resumed. The default handler panics with the
+//! [`Fault`] as payload, so [`panic::set_hook`] sees hardware faults like
+//! any other panic. Their [`PanicInfo::location`] is inside the standard
+//! library though; the default hook prints the address of the faulting
+//! instruction in its place, and custom hooks can get it from the `Fault`:
+//!
+//! ```no_run
+//! #![feature(rvl_ios_exception)]
+//! use std::panic;
+//! use std::os::rvl_ios::exception::Fault;
+//!
+//! panic::set_hook(Box::new(|info| {
+//!     if let Some(fault) = info.payload().downcast_ref::<Fault>() {
+//!         eprintln!("crashed: {}", fault);
+//!         for addr in fault.frames() {
+//!             eprintln!("  {:#010x}", addr);
+//!         }
+//!     }
+//! }));
+//! ```
+//!
+//! Exceptions taken while interrupts were disabled, from an interrupt
+//! handler or by a trap instruction (which is how [`intrinsics::abort`] and
+//! thus aborting panics end) still go to libogc's handler, since no Rust
+//! code can safely run at that point.
+//!
+//! [`set_handler`]: fn.set_handler.html
+//! [`Fault`]: struct.Fault.html
+//! [`panic::set_hook`]: ../../../panic/fn.set_hook.html
+//! [`PanicInfo::location`]: ../../../panic/struct.PanicInfo.html#method.location
+//! [`intrinsics::abort`]: ../../../intrinsics/fn.abort.html
+
+#![unstable(feature = "rvl_ios_exception", issue = "0")]
+
+use crate::fmt;
+use crate::sys::exception;
+
+/// The kind of CPU exception that caused a [`Fault`].
+///
+/// [`Fault`]: struct.Fault.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Exception {
+    /// A load or store to an address that isn't mapped (DSI, vector
+    /// `0x300`).
+    DataStorage,
+    /// An instruction fetch from an address that isn't mapped or isn't
+    /// executable (ISI, vector `0x400`).
+    InstructionStorage,
+    /// A misaligned floating point or multiple word access, or a `dcbz` on
+    /// uncached memory (vector `0x600`).
+    Alignment,
+    /// An illegal or privileged instruction (vector `0x700`).
+    Program,
+    /// A floating point exception enabled in the FPSCR and MSR, reported
+    /// through the program exception vector.
+    FloatingPoint,
+}
+
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Exception::DataStorage => "data storage exception",
+            Exception::InstructionStorage => "instruction storage exception",
+            Exception::Alignment => "alignment exception",
+            Exception::Program => "program exception",
+            Exception::FloatingPoint => "floating point exception",
+        })
+    }
+}
+
+/// The state of the CPU when an exception occurred.
+///
+/// This is the payload of the panic raised by the default exception handler,
+/// and what a handler set with [`set_handler`] is called with.
+///
+/// [`set_handler`]: fn.set_handler.html
+#[derive(Clone, Debug)]
+pub struct Fault {
+    pub(crate) exception: Exception,
+    pub(crate) srr0: u32,
+    pub(crate) srr1: u32,
+    pub(crate) dar: u32,
+    pub(crate) dsisr: u32,
+    pub(crate) frames: Vec<usize>,
+}
+
+impl Fault {
+    /// Returns the kind of exception that occurred.
+    pub fn exception(&self) -> Exception {
+        self.exception
+    }
+
+    /// Returns the faulting address: the data address for data storage and
+    /// alignment exceptions, and the address of the faulting instruction
+    /// otherwise.
+    pub fn address(&self) -> usize {
+        match self.exception {
+            Exception::DataStorage | Exception::Alignment => self.dar as usize,
+            _ => self.srr0 as usize,
+        }
+    }
+
+    /// Returns SRR0, the address of the instruction that caused the
+    /// exception.
+    pub fn srr0(&self) -> u32 {
+        self.srr0
+    }
+
+    /// Returns SRR1, which holds the MSR at the time of the exception along
+    /// with bits describing its cause.
+    pub fn srr1(&self) -> u32 {
+        self.srr1
+    }
+
+    /// Returns the DAR, the data address of a faulting load or store.
+    ///
+    /// This is only meaningful for data storage and alignment exceptions.
+    pub fn dar(&self) -> u32 {
+        self.dar
+    }
+
+    /// Returns the DSISR, which describes the cause of a data storage
+    /// exception or the faulting instruction of an alignment exception.
+    ///
+    /// This is only meaningful for data storage and alignment exceptions.
+    pub fn dsisr(&self) -> u32 {
+        self.dsisr
+    }
+
+    /// Returns the call stack at the time of the exception, innermost first.
+    ///
+    /// The first address is the faulting instruction, the rest are the call
+    /// instructions of its callers, in the format `powerpc-eabi-addr2line`
+    /// expects. The innermost caller may be missing if the faulting function
+    /// hadn't saved its return address yet.
+    pub fn frames(&self) -> &[usize] {
+        &self.frames
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {:#010x}", self.exception, self.srr0)?;
+        match self.exception {
+            Exception::DataStorage | Exception::Alignment => {
+                write!(f, " accessing {:#010x} (DSISR {:#010x})", self.dar, self.dsisr)
+            }
+            _ => write!(f, " (SRR1 {:#010x})", self.srr1),
+        }
+    }
+}
+
+/// Registers the function that is called on a thread that caused a CPU
+/// exception, replacing the previous one.
+///
+/// The handler is called on the faulting thread, in place of the faulting
+/// instruction. As that instruction can't be resumed, the program is
+/// aborted if the handler returns. The default handler panics with the
+/// [`Fault`] as payload; a custom handler usually ends by panicking itself,
+/// or by leaving the program through [`process`].
+///
+/// [`Fault`]: struct.Fault.html
+/// [`process`]: ../process/index.html
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(rvl_ios_exception)]
+/// use std::os::rvl_ios::{exception, process};
+///
+/// exception::set_handler(Box::new(|fault| {
+///     eprintln!("{}", fault);
+///     process::return_to_loader();
+/// }));
+/// ```
+pub fn set_handler(handler: Box<dyn Fn(Fault) + 'static + Sync + Send>) {
+    exception::set_handler(handler)
+}
+
+/// Unregisters the current exception handler, returning it.
+///
+/// The default handler, which panics, is registered in its place, and is
+/// returned if no custom handler was registered.
+pub fn take_handler() -> Box<dyn Fn(Fault) + 'static + Sync + Send> {
+    exception::take_handler()
+}
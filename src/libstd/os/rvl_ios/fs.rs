@@ -18,14 +18,14 @@ pub trait MetadataExt {
     /// Unix platforms. The `os::unix::fs::MetadataExt` trait contains the
     /// cross-Unix abstractions contained within the raw stat.
     ///
-    /// [`stat`]: ../../../../std/os/linux/raw/struct.stat.html
+    /// [`stat`]: ../../../../std/os/rvl_ios/raw/struct.stat.html
     ///
     /// # Examples
     ///
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -47,7 +47,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -64,7 +64,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -81,7 +81,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -98,7 +98,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -115,7 +115,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -132,7 +132,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -149,7 +149,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -169,7 +169,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -186,7 +186,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -205,7 +205,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -222,7 +222,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -241,7 +241,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -258,7 +258,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -277,7 +277,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -294,7 +294,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -311,7 +311,7 @@ pub trait MetadataExt {
     /// ```no_run
     /// use std::fs;
     /// use std::io;
-    /// use std::os::linux::fs::MetadataExt;
+    /// use std::os::rvl_ios::fs::MetadataExt;
     ///
     /// fn main() -> io::Result<()> {
     ///     let meta = fs::metadata("some_file")?;
@@ -328,7 +328,7 @@ impl MetadataExt for Metadata {
     #[allow(deprecated)]
     fn as_raw_stat(&self) -> &raw::stat {
         unsafe {
-            &*(self.as_inner().as_inner() as *const libc::stat64
+            &*(self.as_inner().as_inner() as *const libc::stat
                                           as *const raw::stat)
         }
     }
@@ -360,19 +360,22 @@ impl MetadataExt for Metadata {
         self.as_inner().as_inner().st_atime as i64
     }
     fn st_atime_nsec(&self) -> i64 {
-        self.as_inner().as_inner().st_atime_nsec as i64
+        // newlib only records whole seconds
+        0
     }
     fn st_mtime(&self) -> i64 {
         self.as_inner().as_inner().st_mtime as i64
     }
     fn st_mtime_nsec(&self) -> i64 {
-        self.as_inner().as_inner().st_mtime_nsec as i64
+        // newlib only records whole seconds
+        0
     }
     fn st_ctime(&self) -> i64 {
         self.as_inner().as_inner().st_ctime as i64
     }
     fn st_ctime_nsec(&self) -> i64 {
-        self.as_inner().as_inner().st_ctime_nsec as i64
+        // newlib only records whole seconds
+        0
     }
     fn st_blksize(&self) -> u64 {
         self.as_inner().as_inner().st_blksize as u64
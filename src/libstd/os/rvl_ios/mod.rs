@@ -3,4 +3,11 @@
 #![stable(feature = "raw_ext", since = "1.1.0")]
 
 pub mod raw;
-//pub mod fs;
+pub mod alloc;
+pub mod dma;
+pub mod exception;
+pub mod fs;
+pub mod process;
+pub mod rand;
+pub mod stdio;
+pub mod thread;
@@ -0,0 +1,84 @@
+//! Leaving the running program on rvl-ios.
+//!
+//! IOS only ever runs one program on the PowerPC, so instead of spawning
+//! children, a program ends by handing the console to someone else: the
+//! loader that started it, the System Menu, or another executable.
+//!
+//! Like [`process::exit`], none of these functions return, and no
+//! destructors on the current stack or any other thread's stack are run.
+//! Flush [`io::stdout`] first if it may still hold output.
+//!
+//! [`process::exit`]: ../../../process/fn.exit.html
+//! [`io::stdout`]: ../../../io/fn.stdout.html
+
+#![unstable(feature = "rvl_ios_process", issue = "0")]
+
+use crate::ffi::OsStr;
+use crate::io;
+use crate::path::Path;
+use crate::sys::loader;
+
+/// Returns to the loader that started the program, usually the Homebrew
+/// Channel.
+///
+/// Loaders that support this leave a reload stub in low memory. If there is
+/// none, this returns to the System Menu instead.
+pub fn return_to_loader() -> ! {
+    loader::return_to_loader()
+}
+
+/// Returns to the Wii System Menu.
+pub fn return_to_menu() -> ! {
+    loader::return_to_menu()
+}
+
+/// Resets the console, as if the reset button had been pressed.
+pub fn reset() -> ! {
+    loader::reset()
+}
+
+/// Turns the console off, or puts it into standby if WiiConnect24 is enabled
+/// in its settings.
+pub fn power_off() -> ! {
+    loader::power_off()
+}
+
+/// Replaces the running program with the DOL or ELF executable at `path`.
+///
+/// The new program is passed `path` and `args` the same way the Homebrew
+/// Channel passes them, so [`env::args`] returns the path followed by `args`
+/// on the other side. This requires the executable to be linked with
+/// libogc's crt0, which reserves room for them; other executables start
+/// without arguments.
+///
+/// The executable is read and checked before anything else happens, so this
+/// only returns if that fails, with the error that occurred. Once it
+/// succeeded, libogc is shut down the same way as for a return to the
+/// loader.
+///
+/// The executable is staged in MEM2 while the program is still running,
+/// through [`Mem2Alloc`], so it needs room for all of it: either in the
+/// region set up with `Mem2Alloc::reserve`, or left in MEM2's arena. Memory
+/// taken from the arena for staging is given back when this returns, as long
+/// as `Mem2Alloc` has nothing else allocated, so `Mem2Alloc::reserve` still
+/// works after a failed chainload.
+///
+/// [`env::args`]: ../../../env/fn.args.html
+/// [`Mem2Alloc`]: ../alloc/struct.Mem2Alloc.html
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(rvl_ios_process)]
+/// use std::os::rvl_ios::process;
+///
+/// let err = process::chainload("sd:/apps/emulator/boot.dol", &["sd:/roms/game.nes"]);
+/// eprintln!("couldn't start the emulator: {}", err);
+/// ```
+pub fn chainload<P, I, S>(path: P, args: I) -> io::Error
+    where P: AsRef<Path>, I: IntoIterator<Item = S>, S: AsRef<OsStr>
+{
+    let args: Vec<S> = args.into_iter().collect();
+    let args: Vec<&OsStr> = args.iter().map(|arg| arg.as_ref()).collect();
+    loader::chainload(path.as_ref(), &args)
+}
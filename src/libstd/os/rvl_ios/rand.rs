@@ -0,0 +1,31 @@
+//! Random numbers on rvl-ios.
+
+#![unstable(feature = "rvl_ios_rand", issue = "0")]
+
+use crate::sys;
+
+/// Fills `buf` with random bytes.
+///
+/// This is the source the standard library seeds `HashMap`s from. The bytes
+/// are generated by the AES engine of IOS in counter mode, keyed with a SHA-1
+/// digest of a signature made by IOS, which draws on its hardware seeded
+/// generator, and the timing jitter of requests to it. Gathering them takes
+/// a little while, so applications needing lots of random data should use
+/// them to seed a userspace generator rather than calling this in a loop.
+///
+/// # Panics
+///
+/// Panics if the SHA or AES engine can't be used.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(rvl_ios_rand)]
+/// use std::os::rvl_ios::rand;
+///
+/// let mut seed = [0u8; 32];
+/// rand::fill_bytes(&mut seed);
+/// ```
+pub fn fill_bytes(buf: &mut [u8]) {
+    sys::rand::fill_bytes(buf)
+}
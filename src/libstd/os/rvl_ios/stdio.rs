@@ -0,0 +1,82 @@
+//! Choosing where standard output goes on rvl-ios.
+//!
+//! Out of the box `stdout`, `stderr` and panic messages are written to
+//! newlib's descriptors 1 and 2, which only lead somewhere once libogc's
+//! framebuffer console has been initialized. [`set_sink`] redirects all of
+//! them to one of the other debugging channels a console offers.
+//!
+//! [`set_sink`]: fn.set_sink.html
+
+#![unstable(feature = "rvl_ios_stdio", issue = "0")]
+
+use crate::io;
+use crate::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use crate::sys::stdio;
+
+/// A destination for standard output, standard error and panic messages.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sink {
+    /// libogc's framebuffer console. This is the default.
+    ///
+    /// The console has to be set up, e.g. with `CON_InitEx`, before anything
+    /// shows up on screen.
+    Console,
+    /// A USB Gecko plugged into the memory card slot with the given EXI
+    /// channel (`0` for slot A, `1` for slot B).
+    UsbGecko(i32),
+    /// The debug UART that Dolphin shows as OSReport output in its log
+    /// window. Real hardware has nothing attached to it.
+    Dolphin,
+    /// UDP datagrams to the given address, e.g. a `nc -ul` on a PC.
+    ///
+    /// The network has to be initialized before calling [`set_sink`].
+    ///
+    /// [`set_sink`]: fn.set_sink.html
+    Udp(SocketAddr),
+}
+
+/// Sends everything written to `stdout` and `stderr`, as well as panic
+/// messages, to `sink` from now on.
+///
+/// Output already buffered by [`io::stdout`] is written to the new sink the
+/// next time it is flushed. The previous sink is never dropped, since other
+/// threads may still be writing to it, so a replaced UDP sink keeps its
+/// socket open.
+///
+/// # Errors
+///
+/// Fails if no USB Gecko answers on the requested channel, or if the UDP
+/// socket cannot be created. The previous sink stays in place in that case.
+///
+/// [`io::stdout`]: ../../../io/fn.stdout.html
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(rvl_ios_stdio)]
+/// use std::os::rvl_ios::stdio::{self, Sink};
+///
+/// if stdio::set_sink(Sink::UsbGecko(1)).is_err() {
+///     stdio::set_sink(Sink::Dolphin).unwrap();
+/// }
+/// println!("hello from the Wii");
+/// ```
+pub fn set_sink(sink: Sink) -> io::Result<()> {
+    let sink = match sink {
+        Sink::Console => stdio::Sink::Console,
+        Sink::UsbGecko(channel) => {
+            if unsafe { !ogc_sys::usb_isgeckoalive(channel) } {
+                return Err(io::Error::new(io::ErrorKind::NotFound,
+                                          "no USB Gecko found on that channel"));
+            }
+            stdio::Sink::UsbGecko(channel)
+        }
+        Sink::Dolphin => stdio::Sink::Dolphin,
+        Sink::Udp(addr) => {
+            let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+            stdio::Sink::Udp { socket, addr }
+        }
+    };
+    stdio::set_sink(sink);
+    Ok(())
+}
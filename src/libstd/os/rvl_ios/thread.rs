@@ -0,0 +1,67 @@
+//! rvl-ios-specific extensions to primitives in the `std::thread` module.
+
+#![unstable(feature = "rvl_ios_thread_ext", issue = "0")]
+
+use crate::ffi::CString;
+use crate::sys;
+use crate::thread::Builder;
+
+/// The raw LWP thread handle, as used by the `LWP_*` functions of libogc.
+pub type RawLwp = ogc_sys::lwp_t;
+
+/// The highest priority an LWP thread can run at (`LWP_PRIO_HIGHEST`).
+///
+/// Priorities range from `0` (`LWP_PRIO_IDLE`) up to this value. A runnable
+/// thread always preempts every thread with a lower priority.
+pub const MAX_PRIORITY: u8 = sys::thread::MAX_PRIORITY;
+
+/// rvl-ios-specific extensions to [`thread::Builder`].
+///
+/// [`thread::Builder`]: ../../../../std/thread/struct.Builder.html
+pub trait BuilderExt {
+    /// Sets the LWP priority the new thread starts with.
+    ///
+    /// Threads spawned without a priority run at priority `64`, the one the
+    /// main thread starts at.
+    ///
+    /// # Panics
+    ///
+    /// Spawning the thread panics if `priority` is above [`MAX_PRIORITY`].
+    ///
+    /// [`MAX_PRIORITY`]: constant.MAX_PRIORITY.html
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(rvl_ios_thread_ext)]
+    /// use std::thread;
+    /// use std::os::rvl_ios::thread::BuilderExt;
+    ///
+    /// let audio = thread::Builder::new()
+    ///     .name("audio".into())
+    ///     .priority(100)
+    ///     .spawn(|| {
+    ///         // mix the next buffer
+    ///     })
+    ///     .unwrap();
+    /// ```
+    fn priority(self, priority: u8) -> Self;
+}
+
+impl BuilderExt for Builder {
+    fn priority(self, priority: u8) -> Builder {
+        self.set_priority(priority)
+    }
+}
+
+/// Returns the name of a running thread spawned through `std::thread`.
+///
+/// Unlike [`Thread::name`] this works for any LWP thread, not only the
+/// calling one, which makes it useful for debugging tools. Threads that
+/// were not given a name, were not spawned by the standard library, or
+/// have finished yield `None`.
+///
+/// [`Thread::name`]: ../../../../std/thread/struct.Thread.html#method.name
+pub fn name_of(thread: RawLwp) -> Option<CString> {
+    sys::thread::name_of(thread)
+}
@@ -16,7 +16,7 @@ use crate::mem;
 use crate::ptr;
 use crate::raw;
 use crate::sys::stdio::panic_output;
-//use crate::sys_common::rwlock::RWLock;
+use crate::sys_common::rwlock::RWLock;
 use crate::sys_common::thread_info;
 use crate::sys_common::util;
 use crate::thread;
@@ -56,7 +56,7 @@ enum Hook {
     Custom(*mut (dyn Fn(&PanicInfo<'_>) + 'static + Sync + Send)),
 }
 
-//static HOOK_LOCK: RWLock = RWLock::new();
+static HOOK_LOCK: RWLock = RWLock::new();
 static mut HOOK: Hook = Hook::Default;
 
 /// Registers a custom panic hook, replacing any that was previously registered.
@@ -99,10 +99,10 @@ pub fn set_hook(hook: Box<dyn Fn(&PanicInfo<'_>) + 'static + Sync + Send>) {
     }
 
     unsafe {
-        //HOOK_LOCK.write();
-        //HOOK_LOCK.write_unlock();
+        HOOK_LOCK.write();
         let old_hook = HOOK;
         HOOK = Hook::Custom(Box::into_raw(hook));
+        HOOK_LOCK.write_unlock();
 
         if let Hook::Custom(ptr) = old_hook {
             Box::from_raw(ptr);
@@ -144,10 +144,10 @@ pub fn take_hook() -> Box<dyn Fn(&PanicInfo<'_>) + 'static + Sync + Send> {
     }
 
     unsafe {
-        //HOOK_LOCK.write();
-        //HOOK_LOCK.write_unlock();
+        HOOK_LOCK.write();
         let hook = HOOK;
         HOOK = Hook::Default;
+        HOOK_LOCK.write_unlock();
 
         match hook {
             Hook::Default => Box::new(default_hook),
@@ -171,18 +171,36 @@ fn default_hook(info: &PanicInfo<'_>) {
 
     let location = info.location().unwrap(); // The current implementation always returns Some
 
+    // CPU exceptions are raised with the `Fault` describing them as payload.
+    // The panic location is in the exception handler then, so the faulting
+    // instruction is reported instead.
+    #[cfg(target_os = "rvl-ios")]
+    let fault = info.payload()
+        .downcast_ref::<crate::os::rvl_ios::exception::Fault>()
+        .map(|fault| (fault.to_string(), fault.srr0()));
+    #[cfg(not(target_os = "rvl-ios"))]
+    let fault: Option<(String, u32)> = None;
+
     let msg = match info.payload().downcast_ref::<&'static str>() {
         Some(s) => *s,
         None => match info.payload().downcast_ref::<String>() {
             Some(s) => &s[..],
-            None => "Box<Any>",
+            None => match fault {
+                Some((ref s, _)) => &s[..],
+                None => "Box<Any>",
+            },
         },
     };
     let thread = thread_info::current_thread();
     let name = thread.as_ref().and_then(|t| t.name()).unwrap_or("<unnamed>");
 
     let write = |err: &mut dyn crate::io::Write| {
-        let _ = writeln!(err, "thread '{}' panicked at '{}', {}", name, msg, location);
+        let _ = match fault {
+            Some((_, srr0)) => {
+                writeln!(err, "thread '{}' panicked at '{}', {:#010x}", name, msg, srr0)
+            }
+            None => writeln!(err, "thread '{}' panicked at '{}', {}", name, msg, location),
+        };
 
         #[cfg(feature = "backtrace")]
         {
@@ -216,16 +234,14 @@ fn default_hook(info: &PanicInfo<'_>) {
 #[doc(hidden)]
 #[unstable(feature = "update_panic_count", issue = "0")]
 pub fn update_panic_count(amt: isize) -> usize {
-    // use crate::cell::Cell;
-    // thread_local! { static PANIC_COUNT: Cell<usize> = Cell::new(0) }
-
-    // PANIC_COUNT.with(|c| {
-    //     let next = (c.get() as isize + amt) as usize;
-    //     c.set(next);
-    //     return next;
-    // })
-    
-    unimplemented!()
+    use crate::cell::Cell;
+    thread_local! { static PANIC_COUNT: Cell<usize> = Cell::new(0) }
+
+    PANIC_COUNT.with(|c| {
+        let next = (c.get() as isize + amt) as usize;
+        c.set(next);
+        return next;
+    })
 }
 
 #[cfg(test)]
@@ -438,6 +454,11 @@ fn rust_panic_with_hook(
 ) -> ! {
     let (file, line, col) = *file_line_col;
 
+    // A panic is often the first thing to run after the stack overflowed,
+    // and the hook is about to use plenty more of it.
+    #[cfg(target_os = "rvl-ios")]
+    crate::sys::stack_overflow::check();
+
     let panics = update_panic_count(1);
 
     // If this is the third nested call (e.g., panics == 2, this is 0-indexed),
@@ -459,9 +480,7 @@ fn rust_panic_with_hook(
             Location::internal_constructor(file, line, col),
         );
 
-        // HOOK_LOCK.read();
-        // HOOK_LOCK.read_unlock();
-
+        HOOK_LOCK.read();
         match HOOK {
             // Some platforms know that printing to stderr won't ever actually
             // print anything, and if that's the case we can skip the default
@@ -476,6 +495,7 @@ fn rust_panic_with_hook(
                 (*ptr)(&info);
             }
         };
+        HOOK_LOCK.read_unlock();
     }
 
     if panics > 1 {
@@ -73,7 +73,7 @@ use crate::borrow::{Borrow, Cow};
 use crate::cmp;
 use crate::error::Error;
 use crate::fmt;
-//use crate::fs;
+use crate::fs;
 use crate::hash::{Hash, Hasher};
 use crate::io;
 use crate::iter::{self, FusedIterator};
@@ -100,7 +100,8 @@ use crate::sys::path::{is_sep_byte, is_verbatim_sep, parse_prefix, MAIN_SEP_STR}
 // Windows Prefixes
 ////////////////////////////////////////////////////////////////////////////////
 
-/// Windows path prefixes, e.g., `C:` or `\\server\share`.
+/// Windows path prefixes, e.g., `C:` or `\\server\share`, and rvl-ios device
+/// prefixes, e.g., `sd:`.
 ///
 /// Windows uses a variety of path prefix styles, including references to drive
 /// volumes (like `C:`), network shared folders (like `\\server\share`), and
@@ -108,6 +109,13 @@ use crate::sys::path::{is_sep_byte, is_verbatim_sep, parse_prefix, MAIN_SEP_STR}
 /// `\\?\`), in which case `/` is *not* treated as a separator and essentially
 /// no normalization is performed.
 ///
+/// On rvl-ios, paths may start with the name of the devoptab device they live
+/// on (like `sd:` or `usb:`), which is represented by [`Device`]. Adding that
+/// variant is a breaking change for code matching on `Prefix` exhaustively,
+/// which needs a wildcard arm to build for rvl-ios.
+///
+/// [`Device`]: #variant.Device
+///
 /// # Examples
 ///
 /// ```
@@ -184,6 +192,16 @@ pub enum Prefix<'a> {
     /// Prefix `C:` for the given disk drive.
     #[stable(feature = "rust1", since = "1.0.0")]
     Disk(#[stable(feature = "rust1", since = "1.0.0")] u8),
+
+    /// Prefix naming a devoptab device on rvl-ios, e.g. `sd:` or `usb:`.
+    ///
+    /// Device prefixes consist of the device name immediately followed by
+    /// `:`. Like [`Disk`] prefixes they have no implicit root, so `sd:/apps`
+    /// is absolute while `sd:apps` is not.
+    ///
+    /// [`Disk`]: #variant.Disk
+    #[unstable(feature = "rvl_ios_path_prefix", issue = "0")]
+    Device(#[unstable(feature = "rvl_ios_path_prefix", issue = "0")] &'a OsStr),
 }
 
 impl<'a> Prefix<'a> {
@@ -202,6 +220,7 @@ impl<'a> Prefix<'a> {
             UNC(x, y) => 2 + os_str_len(x) + if os_str_len(y) > 0 { 1 + os_str_len(y) } else { 0 },
             DeviceNS(x) => 4 + os_str_len(x),
             Disk(_) => 2,
+            Device(x) => os_str_len(x) + 1,
         }
     }
 
@@ -233,7 +252,7 @@ impl<'a> Prefix<'a> {
     #[inline]
     fn is_drive(&self) -> bool {
         match *self {
-            Prefix::Disk(_) => true,
+            Prefix::Disk(_) | Prefix::Device(_) => true,
             _ => false,
         }
     }
@@ -1185,7 +1204,8 @@ impl PathBuf {
         // in general, a separator is needed if the rightmost byte is not a separator
         let mut need_sep = self.as_mut_vec().last().map(|c| !is_sep_byte(*c)).unwrap_or(false);
 
-        // in the special case of `C:` on Windows, do *not* add a separator
+        // in the special case of `C:` on Windows (or `sd:` on rvl-ios), do *not*
+        // add a separator
         {
             let comps = self.components();
             if comps.prefix_len() > 0
@@ -2330,10 +2350,10 @@ impl Path {
     /// let metadata = path.metadata().expect("metadata call failed");
     /// println!("{:?}", metadata.file_type());
     /// ```
-    // #[stable(feature = "path_ext", since = "1.5.0")]
-    // pub fn metadata(&self) -> io::Result<fs::Metadata> {
-    //     fs::metadata(self)
-    // }
+    #[stable(feature = "path_ext", since = "1.5.0")]
+    pub fn metadata(&self) -> io::Result<fs::Metadata> {
+        fs::metadata(self)
+    }
 
     /// Queries the metadata about a file without following symlinks.
     ///
@@ -2350,10 +2370,10 @@ impl Path {
     /// let metadata = path.symlink_metadata().expect("symlink_metadata call failed");
     /// println!("{:?}", metadata.file_type());
     /// ```
-    // #[stable(feature = "path_ext", since = "1.5.0")]
-    // pub fn symlink_metadata(&self) -> io::Result<fs::Metadata> {
-    //     fs::symlink_metadata(self)
-    // }
+    #[stable(feature = "path_ext", since = "1.5.0")]
+    pub fn symlink_metadata(&self) -> io::Result<fs::Metadata> {
+        fs::symlink_metadata(self)
+    }
 
     /// Returns the canonical, absolute form of the path with all intermediate
     /// components normalized and symbolic links resolved.
@@ -2370,10 +2390,10 @@ impl Path {
     /// let path = Path::new("/foo/test/../test/bar.rs");
     /// assert_eq!(path.canonicalize().unwrap(), PathBuf::from("/foo/test/bar.rs"));
     /// ```
-    // #[stable(feature = "path_ext", since = "1.5.0")]
-    // pub fn canonicalize(&self) -> io::Result<PathBuf> {
-    //     fs::canonicalize(self)
-    // }
+    #[stable(feature = "path_ext", since = "1.5.0")]
+    pub fn canonicalize(&self) -> io::Result<PathBuf> {
+        fs::canonicalize(self)
+    }
 
     /// Reads a symbolic link, returning the file that the link points to.
     ///
@@ -2389,10 +2409,10 @@ impl Path {
     /// let path = Path::new("/laputa/sky_castle.rs");
     /// let path_link = path.read_link().expect("read_link call failed");
     /// ```
-    // #[stable(feature = "path_ext", since = "1.5.0")]
-    // pub fn read_link(&self) -> io::Result<PathBuf> {
-    //     fs::read_link(self)
-    // }
+    #[stable(feature = "path_ext", since = "1.5.0")]
+    pub fn read_link(&self) -> io::Result<PathBuf> {
+        fs::read_link(self)
+    }
 
     /// Returns an iterator over the entries within a directory.
     ///
@@ -2417,10 +2437,10 @@ impl Path {
     ///     }
     /// }
     /// ```
-    // #[stable(feature = "path_ext", since = "1.5.0")]
-    // pub fn read_dir(&self) -> io::Result<fs::ReadDir> {
-    //     fs::read_dir(self)
-    // }
+    #[stable(feature = "path_ext", since = "1.5.0")]
+    pub fn read_dir(&self) -> io::Result<fs::ReadDir> {
+        fs::read_dir(self)
+    }
 
     /// Returns `true` if the path points at an existing entity.
     ///
@@ -2443,10 +2463,10 @@ impl Path {
     /// check errors, call [fs::metadata].
     ///
     /// [fs::metadata]: ../../std/fs/fn.metadata.html
-    // #[stable(feature = "path_ext", since = "1.5.0")]
-    // pub fn exists(&self) -> bool {
-    //     fs::metadata(self).is_ok()
-    // }
+    #[stable(feature = "path_ext", since = "1.5.0")]
+    pub fn exists(&self) -> bool {
+        fs::metadata(self).is_ok()
+    }
 
     /// Returns `true` if the path exists on disk and is pointing at a regular file.
     ///
@@ -2472,10 +2492,10 @@ impl Path {
     ///
     /// [fs::metadata]: ../../std/fs/fn.metadata.html
     /// [fs::Metadata::is_file]: ../../std/fs/struct.Metadata.html#method.is_file
-    // #[stable(feature = "path_ext", since = "1.5.0")]
-    // pub fn is_file(&self) -> bool {
-    //     fs::metadata(self).map(|m| m.is_file()).unwrap_or(false)
-    // }
+    #[stable(feature = "path_ext", since = "1.5.0")]
+    pub fn is_file(&self) -> bool {
+        fs::metadata(self).map(|m| m.is_file()).unwrap_or(false)
+    }
 
     /// Returns `true` if the path exists on disk and is pointing at a directory.
     ///
@@ -2501,10 +2521,10 @@ impl Path {
     ///
     /// [fs::metadata]: ../../std/fs/fn.metadata.html
     /// [fs::Metadata::is_dir]: ../../std/fs/struct.Metadata.html#method.is_dir
-    // #[stable(feature = "path_ext", since = "1.5.0")]
-    // pub fn is_dir(&self) -> bool {
-    //     fs::metadata(self).map(|m| m.is_dir()).unwrap_or(false)
-    // }
+    #[stable(feature = "path_ext", since = "1.5.0")]
+    pub fn is_dir(&self) -> bool {
+        fs::metadata(self).map(|m| m.is_dir()).unwrap_or(false)
+    }
 
     /// Converts a [`Box<Path>`][`Box`] into a [`PathBuf`] without copying or
     /// allocating.
@@ -3696,6 +3716,60 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(target_os = "rvl-ios")]
+    pub fn test_decompositions_rvl_ios() {
+        t!("sd:",
+        iter: ["sd:"],
+        has_root: false,
+        is_absolute: false,
+        parent: None,
+        file_name: None,
+        file_stem: None,
+        extension: None
+        );
+
+        t!("sd:/",
+        iter: ["sd:", "/"],
+        has_root: true,
+        is_absolute: true,
+        parent: None,
+        file_name: None,
+        file_stem: None,
+        extension: None
+        );
+
+        t!("sd:/apps/game/data.bin",
+        iter: ["sd:", "/", "apps", "game", "data.bin"],
+        has_root: true,
+        is_absolute: true,
+        parent: Some("sd:/apps/game"),
+        file_name: Some("data.bin"),
+        file_stem: Some("data"),
+        extension: Some("bin")
+        );
+
+        t!("usb:apps",
+        iter: ["usb:", "apps"],
+        has_root: false,
+        is_absolute: false,
+        parent: Some("usb:"),
+        file_name: Some("apps"),
+        file_stem: Some("apps"),
+        extension: None
+        );
+
+        t!("apps/a:b",
+        iter: ["apps", "a:b"],
+        has_root: false,
+        is_absolute: false,
+        parent: Some("apps"),
+        file_name: Some("a:b"),
+        file_stem: Some("a:b"),
+        extension: None
+        );
+    }
+
     #[test]
     pub fn test_stem_ext() {
         t!("foo",
@@ -3821,6 +3895,12 @@ mod tests {
 
             tp!("\\\\?\\C:", "foo", "\\\\?\\C:\\foo"); // this is a weird one
         }
+
+        if cfg!(target_os = "rvl-ios") {
+            tp!("sd:/apps", "game", "sd:/apps/game");
+            tp!("sd:/apps", "usb:/data", "usb:/data");
+            tp!("sd:", "apps", "sd:apps");
+        }
     }
 
     #[test]
@@ -4088,6 +4168,36 @@ mod tests {
             relative_from: Some("")
             );
         }
+
+        if cfg!(target_os = "rvl-ios") {
+            tc!("sd:/apps/game/boot.dol", "sd:/apps",
+            eq: false,
+            starts_with: true,
+            ends_with: false,
+            relative_from: Some("game/boot.dol")
+            );
+
+            tc!("sd:/apps/game", "sd:/apps/",
+            eq: false,
+            starts_with: true,
+            ends_with: false,
+            relative_from: Some("game")
+            );
+
+            tc!("sd:/apps", "usb:/apps",
+            eq: false,
+            starts_with: false,
+            ends_with: false,
+            relative_from: None
+            );
+
+            tc!("sd:apps", "sd:/",
+            eq: false,
+            starts_with: false,
+            ends_with: false,
+            relative_from: None
+            );
+        }
     }
 
     #[test]
@@ -1647,7 +1647,8 @@ impl Termination for ExitCode {
     }
 }
 
-#[cfg(all(test, not(any(target_os = "cloudabi", target_os = "emscripten", target_env = "sgx"))))]
+#[cfg(all(test, not(any(target_os = "cloudabi", target_os = "emscripten",
+                         target_env = "sgx", target_os = "rvl-ios"))))]
 mod tests {
     use crate::io::prelude::*;
 
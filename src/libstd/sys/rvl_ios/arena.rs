@@ -0,0 +1,305 @@
+//! Heaps placed in one specific memory bank of the console.
+//!
+//! newlib's `malloc` grows its heap through `sbrk`, which libogc serves from
+//! the bottom of the MEM1 arena and only moves on to MEM2 once MEM1 runs
+//! out. An `Arena` instead takes memory from the *top* of one bank's arena
+//! by lowering the arena's upper bound, so both can coexist, and manages it
+//! itself. It takes only what it needs when an allocation doesn't fit, at
+//! least `GROW_MIN` at a time, unless it was set up with a fixed region
+//! through `claim`.
+//!
+//! Free memory is kept in a singly linked list of blocks sorted by address,
+//! each starting with a `FreeBlock` header. Allocated blocks carry no header
+//! since `GlobalAlloc::dealloc` is told the layout again. Every block address
+//! and size is a multiple of `BLOCK_ALIGN`, a cache line, so no allocation
+//! shares a line with another one. That keeps cache maintenance for DMA from
+//! clobbering neighbouring data.
+//!
+//! The list is only ever touched with interrupts disabled, which on the
+//! single-core Broadway excludes every other thread.
+
+use crate::alloc::Layout;
+use crate::cell::UnsafeCell;
+use crate::cmp;
+use crate::io;
+use crate::ptr;
+
+const BLOCK_ALIGN: usize = 32;
+
+// The least an arena grows by, so that small allocations don't each take
+// their own region from the bank's arena.
+const GROW_MIN: usize = 64 * 1024;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Bank {
+    Mem1,
+    Mem2,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Stats {
+    pub size: usize,
+    pub free: usize,
+    pub largest_free: usize,
+    pub high_water: usize,
+}
+
+struct FreeBlock {
+    size: usize,
+    next: *mut FreeBlock,
+}
+
+struct State {
+    // Whether `claim` set up a fixed region, which is never grown.
+    reserved: bool,
+    free_list: *mut FreeBlock,
+    size: usize,
+    used: usize,
+    high_water: usize,
+}
+
+const EMPTY: State = State {
+    reserved: false,
+    free_list: ptr::null_mut(),
+    size: 0,
+    used: 0,
+    high_water: 0,
+};
+
+pub struct Arena {
+    bank: Bank,
+    state: UnsafeCell<State>,
+}
+
+unsafe impl Sync for Arena {}
+
+pub static MEM1: Arena = Arena::new(Bank::Mem1);
+pub static MEM2: Arena = Arena::new(Bank::Mem2);
+
+// Disables interrupts for the duration of `f`.
+unsafe fn critical<R>(f: impl FnOnce() -> R) -> R {
+    let level = ogc_sys::IRQ_Disable();
+    let r = f();
+    ogc_sys::IRQ_Restore(level);
+    r
+}
+
+fn round_up(n: usize, align: usize) -> Option<usize> {
+    Some(n.checked_add(align - 1)? & !(align - 1))
+}
+
+fn block_size(layout: &Layout) -> Option<usize> {
+    round_up(cmp::max(layout.size(), 1), BLOCK_ALIGN)
+}
+
+impl Arena {
+    const fn new(bank: Bank) -> Arena {
+        Arena { bank, state: UnsafeCell::new(EMPTY) }
+    }
+
+    // The usable part of the bank's arena.
+    unsafe fn bounds(&self) -> (usize, usize) {
+        let (lo, hi) = match self.bank {
+            Bank::Mem1 => (ogc_sys::SYS_GetArena1Lo(), ogc_sys::SYS_GetArena1Hi()),
+            Bank::Mem2 => (ogc_sys::SYS_GetArena2Lo(), ogc_sys::SYS_GetArena2Hi()),
+        };
+        let lo = round_up(lo as usize, BLOCK_ALIGN).unwrap_or(usize::max_value());
+        (lo, hi as usize & !(BLOCK_ALIGN - 1))
+    }
+
+    unsafe fn set_hi(&self, hi: usize) {
+        match self.bank {
+            Bank::Mem1 => ogc_sys::SYS_SetArena1Hi(hi as *mut _),
+            Bank::Mem2 => ogc_sys::SYS_SetArena2Hi(hi as *mut _),
+        }
+    }
+
+    // Takes `size` bytes, a multiple of `BLOCK_ALIGN`, from the top of the
+    // bank's arena and adds them to the free list.
+    unsafe fn take(&self, state: &mut State, size: usize) -> bool {
+        let (lo, hi) = self.bounds();
+        if size > hi.saturating_sub(lo) {
+            return false;
+        }
+        let start = hi - size;
+        self.set_hi(start);
+        state.size += size;
+        if size > 0 {
+            insert_free(state, start, size);
+        }
+        true
+    }
+
+    /// Sets the arena up with a fixed region of `size` bytes from the top of
+    /// the bank's arena, which it never grows beyond. Fails if the arena
+    /// already took memory before or doesn't have `size` bytes left.
+    pub fn claim(&self, size: usize) -> io::Result<()> {
+        unsafe {
+            critical(|| {
+                let state = &mut *self.state.get();
+                if state.reserved || state.size != 0 {
+                    return Err(io::Error::new(io::ErrorKind::AlreadyExists,
+                                              "the arena has already been set up"));
+                }
+                match round_up(size, BLOCK_ALIGN) {
+                    Some(size) if self.take(state, size) => {
+                        state.reserved = true;
+                        Ok(())
+                    }
+                    _ => Err(io::Error::new(io::ErrorKind::Other,
+                                            "not enough memory left in the arena")),
+                }
+            })
+        }
+    }
+
+    // Takes enough from the bank's arena for an allocation of `size` bytes
+    // aligned to `align` to fit, unless the region is fixed.
+    unsafe fn grow(&self, state: &mut State, size: usize, align: usize) -> bool {
+        if state.reserved {
+            return false;
+        }
+        let needed = match size.checked_add(align - BLOCK_ALIGN) {
+            Some(needed) => needed,
+            None => return false,
+        };
+        let (lo, hi) = self.bounds();
+        let available = hi.saturating_sub(lo);
+        needed <= available && self.take(state, cmp::max(needed, cmp::min(GROW_MIN, available)))
+    }
+
+    /// Gives the memory taken by a growing arena back to the bank's arena
+    /// once nothing is allocated from it anymore, as long as nobody took
+    /// memory from the bank's arena below it in the meantime. Fixed regions
+    /// set up through `claim` are kept.
+    pub fn release(&self) {
+        unsafe {
+            critical(|| {
+                let state = &mut *self.state.get();
+                if state.reserved || state.used != 0 {
+                    return;
+                }
+                // With nothing allocated, contiguous regions have all merged
+                // into a single free block.
+                let block = state.free_list;
+                if block.is_null() || !(*block).next.is_null() || self.bounds().1 != block as usize {
+                    return;
+                }
+                self.set_hi(block as usize + (*block).size);
+                *state = EMPTY;
+            })
+        }
+    }
+
+    pub unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let size = match block_size(&layout) {
+            Some(size) => size,
+            None => return ptr::null_mut(),
+        };
+        let align = cmp::max(layout.align(), BLOCK_ALIGN);
+        critical(|| {
+            let state = &mut *self.state.get();
+            let addr = match first_fit(state, size, align) {
+                Some(addr) => addr,
+                None if self.grow(state, size, align) => {
+                    match first_fit(state, size, align) {
+                        Some(addr) => addr,
+                        None => return ptr::null_mut(),
+                    }
+                }
+                None => return ptr::null_mut(),
+            };
+            state.used += size;
+            state.high_water = cmp::max(state.high_water, state.used);
+            addr as *mut u8
+        })
+    }
+
+    pub unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let size = block_size(&layout).unwrap();
+        critical(|| {
+            let state = &mut *self.state.get();
+            debug_assert!(state.used >= size);
+            state.used -= size;
+            insert_free(state, ptr as usize, size);
+        })
+    }
+
+    pub fn stats(&self) -> Stats {
+        unsafe {
+            critical(|| {
+                let state = &*self.state.get();
+                let mut largest_free = 0;
+                let mut block = state.free_list;
+                while !block.is_null() {
+                    largest_free = cmp::max(largest_free, (*block).size);
+                    block = (*block).next;
+                }
+                Stats {
+                    size: state.size,
+                    free: state.size - state.used,
+                    largest_free,
+                    high_water: state.high_water,
+                }
+            })
+        }
+    }
+}
+
+// First fit. Whatever is left in front of or behind the allocation inside
+// the chosen block stays on the list; both remainders are multiples of
+// `BLOCK_ALIGN` as well.
+unsafe fn first_fit(state: &mut State, size: usize, align: usize) -> Option<usize> {
+    let mut link: *mut *mut FreeBlock = &mut state.free_list;
+    while !(*link).is_null() {
+        let block = *link;
+        let start = block as usize;
+        let end = start + (*block).size;
+        let found = round_up(start, align)
+            .and_then(|addr| Some((addr, addr.checked_add(size)?)))
+            .filter(|&(_, alloc_end)| alloc_end <= end);
+        if let Some((addr, alloc_end)) = found {
+            let mut rest = (*block).next;
+            if alloc_end < end {
+                let tail = alloc_end as *mut FreeBlock;
+                tail.write(FreeBlock { size: end - alloc_end, next: rest });
+                rest = tail;
+            }
+            if addr > start {
+                (*block).size = addr - start;
+                (*block).next = rest;
+            } else {
+                *link = rest;
+            }
+            return Some(addr);
+        }
+        link = &mut (*block).next;
+    }
+    None
+}
+
+// Puts `size` bytes at `addr` on the free list, merging them with the
+// blocks right before and after.
+unsafe fn insert_free(state: &mut State, addr: usize, size: usize) {
+    let mut prev: *mut FreeBlock = ptr::null_mut();
+    let mut next = state.free_list;
+    while !next.is_null() && (next as usize) < addr {
+        prev = next;
+        next = (*next).next;
+    }
+
+    let block = addr as *mut FreeBlock;
+    block.write(FreeBlock { size, next });
+    if !next.is_null() && addr + size == next as usize {
+        (*block).size += (*next).size;
+        (*block).next = (*next).next;
+    }
+    if prev.is_null() {
+        state.free_list = block;
+    } else if prev as usize + (*prev).size == addr {
+        (*prev).size += (*block).size;
+        (*prev).next = (*block).next;
+    } else {
+        (*prev).next = block;
+    }
+}
@@ -16,9 +16,12 @@ pub unsafe fn init(argc: isize, argv: *const *const u8) { imp::init(argc, argv)
 pub unsafe fn cleanup() { imp::cleanup() }
 
 /// Returns the command line arguments
-// pub fn args() -> Args {
-//     imp::args()
-// }
+pub fn args() -> Args {
+    imp::args()
+}
+
+#[cfg(target_os = "rvl-ios")]
+pub use self::imp::parse_command_line;
 
 pub struct Args {
     iter: vec::IntoIter<OsString>,
@@ -102,27 +105,101 @@ mod imp {
     }
 }
 
-#[cfg(any(target_os = "rvl-ios"))]
+// Homebrew loaders don't call `main` with arguments of their own. Instead
+// they write a `struct __argv` into the space libogc's crt0 reserves for it
+// right after the entry point, tagged with `ARGV_MAGIC`, and libogc exposes
+// it as `__system_argv`. Its command line is a run of NUL terminated strings:
+// the path the executable was loaded from, followed by the `<arguments>` of
+// its meta.xml.
+#[cfg(target_os = "rvl-ios")]
 mod imp {
+    use crate::ffi::{CStr, OsString};
+    use crate::marker::PhantomData;
     use crate::ptr;
+    use crate::slice;
+    use crate::sys::ext::ffi::OsStringExt;
     use crate::sys_common::mutex::Mutex;
+    use super::Args;
 
-    static mut ARGC: isize = 0;
-    static mut ARGV: *const *const u8 = ptr::null();
-    // We never call `ENV_LOCK.init()`, so it is UB to attempt to
+    static mut ARGS: *mut Vec<OsString> = ptr::null_mut();
+    // We never call `LOCK.init()`, so it is UB to attempt to
     // acquire this mutex reentrantly!
     static LOCK: Mutex = Mutex::new();
 
     pub unsafe fn init(argc: isize, argv: *const *const u8) {
+        // The argv block lives in memory the program is free to overwrite
+        // later on, so copy the arguments out right away.
+        let args = loader_args().unwrap_or_else(|| main_args(argc, argv));
         let _guard = LOCK.lock();
-        ARGC = argc;
-        ARGV = argv;
+        ARGS = Box::into_raw(box args);
     }
 
     pub unsafe fn cleanup() {
         let _guard = LOCK.lock();
-        ARGC = 0;
-        ARGV = ptr::null();
+        if !ARGS.is_null() {
+            drop(Box::from_raw(ARGS));
+            ARGS = ptr::null_mut();
+        }
+    }
+
+    pub fn args() -> Args {
+        Args {
+            iter: clone().into_iter(),
+            _dont_send_or_sync_me: PhantomData
+        }
+    }
+
+    fn clone() -> Vec<OsString> {
+        unsafe {
+            let _guard = LOCK.lock();
+            if ARGS.is_null() {
+                Vec::new()
+            } else {
+                (*ARGS).clone()
+            }
+        }
+    }
+
+    unsafe fn loader_args() -> Option<Vec<OsString>> {
+        let block = ogc_sys::__system_argv;
+        if block.is_null() || (*block).argvMagic != ogc_sys::ARGV_MAGIC as i32 {
+            return None;
+        }
+        let block = &*block;
+        if block.commandLine.is_null() || block.length <= 0 {
+            return None;
+        }
+        let line = slice::from_raw_parts(block.commandLine as *const u8, block.length as usize);
+        Some(parse_command_line(line))
+    }
+
+    /// Splits a loader command line into its arguments.
+    pub fn parse_command_line(line: &[u8]) -> Vec<OsString> {
+        // Every argument is terminated by a NUL, which leaves an empty
+        // trailing piece after the split, and some loaders pad the line with
+        // extra NULs. Like libogc's own `build_argv`, skip every empty piece
+        // rather than stopping at the first one, which would drop whatever
+        // follows an empty argument.
+        line.split(|&b| b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| OsStringExt::from_vec(arg.to_vec()))
+            .collect()
+    }
+
+    // Without a loader the arguments, if any, come from whoever called main.
+    unsafe fn main_args(argc: isize, argv: *const *const u8) -> Vec<OsString> {
+        if argv.is_null() {
+            return Vec::new();
+        }
+        (0..argc).filter_map(|i| {
+            let arg = *argv.offset(i);
+            if arg.is_null() {
+                None
+            } else {
+                let cstr = CStr::from_ptr(arg as *const libc::c_char);
+                Some(OsStringExt::from_vec(cstr.to_bytes().to_vec()))
+            }
+        }).collect()
     }
 }
 
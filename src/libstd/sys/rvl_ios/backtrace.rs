@@ -0,0 +1,39 @@
+//! Stack walking for backtraces.
+//!
+//! Every function built for the PowerPC EABI starts its frame by storing the
+//! caller's stack pointer at offset 0 of its own, and its return address in
+//! the LR save word at offset 4 of the caller's frame. Following that back
+//! chain from r1 visits every frame without needing any unwind info.
+//!
+//! There's no symbol table in memory to resolve the addresses against, so
+//! they are printed as is, for `powerpc-eabi-addr2line` to translate against
+//! the ELF the program was built from.
+
+/// Whether `addr` can be a stack pointer. Thread stacks come from MEM1 or
+/// MEM2.
+pub fn is_stack_addr(addr: usize) -> bool {
+    addr & 7 == 0 && ((0x8000_0000..0x8180_0000).contains(&addr)
+                      || (0x9000_0000..0x9400_0000).contains(&addr))
+}
+
+/// Calls `f` with the address of the call instruction in every frame of the
+/// current stack, innermost first, for as long as it returns `true`.
+#[inline(never)]
+pub fn trace(mut f: impl FnMut(usize) -> bool) {
+    let mut sp: usize;
+    unsafe {
+        asm!("mr $0, 1" : "=r"(sp));
+    }
+    while is_stack_addr(sp) {
+        let next = unsafe { *(sp as *const usize) };
+        // The stack grows down, and the outermost frame has a null back chain.
+        if next <= sp || !is_stack_addr(next) {
+            break;
+        }
+        let lr = unsafe { *((next + 4) as *const usize) };
+        if lr < 4 || !f(lr - 4) {
+            break;
+        }
+        sp = next;
+    }
+}
@@ -1,26 +1,24 @@
 use crate::cell::UnsafeCell;
+use crate::cmp;
 use crate::sys::mutex::{self, Mutex};
+use crate::sys::stack_overflow;
 use crate::time::Duration;
 
 pub struct Condvar {
-    inner: UnsafeCell<ogc_sys::pthread_cond_t>,
+    inner: UnsafeCell<ogc_sys::cond_t>,
 }
 
 unsafe impl Send for Condvar {}
 unsafe impl Sync for Condvar {}
 
-const TIMESPEC_MAX: ogc_sys::timespec =
-    ogc_sys::timespec { tv_sec: <ogc_sys::time_t>::max_value(), tv_nsec: 1_000_000_000 - 1, __bindgen_padding_0: 0 };
+// The shortest timeout we hand to `LWP_CondTimedWait`. libogc converts the
+// timespec to timebase ticks, and anything that rounds down to zero ticks is
+// treated as "no timeout at all", which would turn a tiny wait into a
+// potentially endless one.
+const MIN_TIMEOUT: Duration = Duration::from_micros(1);
 
-const CLOCK_MONOTONIC: i32 = 4;
-
-fn saturating_cast_to_time_t(value: u64) -> ogc_sys::time_t {
-    if value > <ogc_sys::time_t>::max_value() as u64 {
-        <ogc_sys::time_t>::max_value()
-    } else {
-        value as ogc_sys::time_t
-    }
-}
+// Keeps the tick conversion in libogc from overflowing its 64-bit counter.
+const MAX_TIMEOUT_SECS: u64 = u32::max_value() as u64;
 
 impl Condvar {
     pub const fn new() -> Condvar {
@@ -30,72 +28,53 @@ impl Condvar {
     }
 
     pub unsafe fn init(&mut self) {
-        // use crate::mem;
-        // let mut attr: libc::pthread_condattr_t = mem::uninitialized();
-        // let r = libc::pthread_condattr_init(&mut attr);
-        // assert_eq!(r, 0);
-        // let r = libc::pthread_condattr_setclock(&mut attr, libc::CLOCK_MONOTONIC);
-        // assert_eq!(r, 0);
-        // let r = libc::pthread_condattr_destroy(&mut attr);
-        // assert_eq!(r, 0);
-        // let r = ogc_sys::LWP_CondInit(self.inner.get() as *mut u32);
-        // assert_eq!(r, 0);
+        // libogc condition variables have no attributes to speak of; the
+        // timeout passed to `LWP_CondTimedWait` is always measured against
+        // the timebase, so there is no clock to select either.
+        let r = ogc_sys::LWP_CondInit(self.inner.get());
+        assert_eq!(r, 0);
     }
 
     #[inline]
     pub unsafe fn notify_one(&self) {
-        // let r = libc::pthread_cond_signal(self.inner.get());
-        // let r = ogc_sys::LWP_CondSignal(self.inner.get() as u32);
-        // debug_assert_eq!(r, 0);
+        let r = ogc_sys::LWP_CondSignal(*self.inner.get());
+        debug_assert_eq!(r, 0);
     }
 
     #[inline]
     pub unsafe fn notify_all(&self) {
-        // let r = libc::pthread_cond_broadcast(self.inner.get());
-        // let r = ogc_sys::LWP_CondBroadcast(self.inner.get() as u32);
-        // debug_assert_eq!(r, 0);
+        let r = ogc_sys::LWP_CondBroadcast(*self.inner.get());
+        debug_assert_eq!(r, 0);
     }
 
     #[inline]
     pub unsafe fn wait(&self, mutex: &Mutex) {
-        // let r = libc::pthread_cond_wait(self.inner.get(), mutex::raw(mutex));
-        // let r = ogc_sys::LWP_CondWait(self.inner.get() as u32, mutex::raw(mutex) as u32);
-        // debug_assert_eq!(r, 0);
+        stack_overflow::check();
+        let r = ogc_sys::LWP_CondWait(*self.inner.get(), mutex::handle(mutex));
+        debug_assert_eq!(r, 0);
     }
 
-    // This implementation is used on systems that support pthread_condattr_setclock
-    // where we configure condition variable to use monotonic clock (instead of
-    // default system clock). This approach avoids all problems that result
-    // from changes made to the system time.
+    // Despite its `abstime` parameter, `LWP_CondTimedWait` interprets the
+    // timespec as an interval relative to the moment of the call, so the
+    // duration can be passed through as is. That also means changes to the
+    // system time never affect the wait.
     pub unsafe fn wait_timeout(&self, mutex: &Mutex, dur: Duration) -> bool {
-        use crate::mem;
-
-        let mut now: ogc_sys::timespec = mem::zeroed();
-        let r = ogc_sys::clock_gettime(CLOCK_MONOTONIC as u32, &mut now);
-        assert_eq!(r, 0);
-
-        // Nanosecond calculations can't overflow because both values are below 1e9.
-        let nsec = dur.subsec_nanos() + now.tv_nsec as u32;
-
-        let sec = saturating_cast_to_time_t(dur.as_secs())
-            .checked_add((nsec / 1_000_000_000) as ogc_sys::time_t)
-            .and_then(|s| s.checked_add(now.tv_sec));
-        let nsec = nsec % 1_000_000_000;
-
-        // TODO: FIX TIMED WAIT
-        // let timeout =
-        //     sec.map(|s| ogc_sys::timespec { tv_sec: s, tv_nsec: nsec as _ }).unwrap_or(TIMESPEC_MAX);
-
-        // let r = ogc_sys::LWP_CondTimedWait(self.inner.get() as u32, mutex::raw(mutex) as u32, &timeout);
-        // assert!(r == libc::ETIMEDOUT || r == 0);
-        // r == 0
-        true
+        stack_overflow::check();
+        let dur = cmp::max(dur, MIN_TIMEOUT);
+        let timeout = ogc_sys::timespec {
+            tv_sec: cmp::min(dur.as_secs(), MAX_TIMEOUT_SECS) as ogc_sys::time_t,
+            tv_nsec: dur.subsec_nanos() as _,
+            __bindgen_padding_0: 0,
+        };
+
+        let r = ogc_sys::LWP_CondTimedWait(*self.inner.get(), mutex::handle(mutex), &timeout);
+        assert!(r == libc::ETIMEDOUT || r == 0);
+        r == 0
     }
 
     #[inline]
     pub unsafe fn destroy(&self) {
-        // let r = libc::pthread_cond_destroy(self.inner.get());
-        // let r = ogc_sys::LWP_CondDestroy(self.inner.get() as u32);
-        // debug_assert_eq!(r, 0);
+        let r = ogc_sys::LWP_CondDestroy(*self.inner.get());
+        debug_assert_eq!(r, 0);
     }
 }
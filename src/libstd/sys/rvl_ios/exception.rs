@@ -0,0 +1,188 @@
+//! CPU exception handlers.
+//!
+//! libogc's exception vectors save the interrupted context into a
+//! `frame_context` and call the handler registered for the exception, then
+//! restore the (possibly modified) context with `rfi`. Our handler does as
+//! little as possible at that point: it rewrites the context so that `rfi`
+//! "calls" `raise` on the faulting thread, with the fault's registers as
+//! arguments and a return address right after the faulting instruction, so
+//! the back chain still leads through it. Everything else, from capturing
+//! the backtrace to panicking, then runs as ordinary thread code.
+//!
+//! EX_FP is left alone: it's the floating point unavailable exception,
+//! which libogc relies on to switch FPU contexts lazily. Enabled floating
+//! point exceptions arrive as program exceptions instead.
+
+use crate::os::rvl_ios::exception::{Exception, Fault};
+use crate::sys::backtrace;
+use crate::sys::stack_overflow;
+use crate::sys_common::rwlock::RWLock;
+
+extern "C" {
+    fn __exception_sethandler(exception: u32,
+                              handler: unsafe extern "C" fn(*mut ogc_sys::frame_context));
+    fn c_default_exceptionhandler(frame: *mut ogc_sys::frame_context);
+}
+
+// MSR[EE], external interrupts enabled.
+const MSR_EE: u32 = 0x0000_8000;
+// MSR[FE0] and MSR[FE1], the floating point exception mode.
+const MSR_FE: u32 = 0x0000_0900;
+// Causes of a program exception, as reported in SRR1.
+const SRR1_FP: u32 = 0x0010_0000;
+const SRR1_TRAP: u32 = 0x0002_0000;
+// DSISR bit set when a DSI was caused by the data address breakpoint.
+const DSISR_DABR: u32 = 0x0040_0000;
+
+// Enough to get past the panic machinery and into the program's own frames
+// even for a fault deep inside a callback.
+const MAX_FRAMES: usize = 64;
+
+#[derive(Copy, Clone)]
+enum Handler {
+    Default,
+    Custom(*mut (dyn Fn(Fault) + 'static + Sync + Send)),
+}
+
+static HANDLER_LOCK: RWLock = RWLock::new();
+static mut HANDLER: Handler = Handler::Default;
+
+pub fn set_handler(handler: Box<dyn Fn(Fault) + 'static + Sync + Send>) {
+    unsafe {
+        HANDLER_LOCK.write();
+        let old_handler = HANDLER;
+        HANDLER = Handler::Custom(Box::into_raw(handler));
+        HANDLER_LOCK.write_unlock();
+
+        if let Handler::Custom(ptr) = old_handler {
+            Box::from_raw(ptr);
+        }
+    }
+}
+
+pub fn take_handler() -> Box<dyn Fn(Fault) + 'static + Sync + Send> {
+    unsafe {
+        HANDLER_LOCK.write();
+        let handler = HANDLER;
+        HANDLER = Handler::Default;
+        HANDLER_LOCK.write_unlock();
+
+        match handler {
+            Handler::Default => Box::new(default_handler),
+            Handler::Custom(ptr) => Box::from_raw(ptr),
+        }
+    }
+}
+
+fn default_handler(fault: Fault) {
+    crate::panicking::begin_panic(fault, &(file!(), line!(), column!()))
+}
+
+/// Installs our handlers in place of libogc's for the exceptions a program
+/// can cause by itself.
+pub unsafe fn init() {
+    for &exception in &[ogc_sys::EX_DSI, ogc_sys::EX_ISI, ogc_sys::EX_ALIGN, ogc_sys::EX_PRG] {
+        __exception_sethandler(exception as u32, handle);
+    }
+}
+
+// Runs in exception context, with interrupts disabled and the stack of the
+// faulting thread.
+unsafe extern "C" fn handle(frame: *mut ogc_sys::frame_context) {
+    let frame = &mut *frame;
+    let (dar, dsisr): (u32, u32);
+    asm!("mfspr $0, 19" : "=r"(dar));
+    asm!("mfspr $0, 18" : "=r"(dsisr));
+
+    // The breakpoint only ever guards a stack, see `stack_overflow`.
+    if frame.EXCPT_Number == ogc_sys::EX_DSI as u32 && dsisr & DSISR_DABR != 0 {
+        if !stack_overflow::breakpoint(frame, dar) {
+            c_default_exceptionhandler(frame);
+        }
+        return;
+    }
+    // Code running with interrupts disabled is either an interrupt handler
+    // or holds a critical section that the panic machinery may well need.
+    // A stack pointer outside of any stack means there's nowhere to run
+    // `raise` either.
+    if frame.SRR1 & MSR_EE == 0 || !backtrace::is_stack_addr(frame.GPR[1] as usize) {
+        return c_default_exceptionhandler(frame);
+    }
+    if frame.EXCPT_Number == ogc_sys::EX_PRG as u32 {
+        // Traps are how `intrinsics::abort` ends the program, not faults.
+        if frame.SRR1 & SRR1_TRAP != 0 {
+            return c_default_exceptionhandler(frame);
+        }
+        // The FPSCR still holds the exception, so keep `raise` from taking
+        // it again on its first floating point instruction.
+        if frame.SRR1 & SRR1_FP != 0 {
+            frame.SRR1 &= !MSR_FE;
+        }
+    }
+
+    frame.GPR[3] = frame.EXCPT_Number;
+    frame.GPR[4] = frame.SRR0;
+    frame.GPR[5] = frame.SRR1;
+    frame.GPR[6] = dar;
+    frame.GPR[7] = dsisr;
+    frame.LR = frame.SRR0.wrapping_add(4);
+    frame.SRR0 = raise as usize as u32;
+}
+
+// Where the faulting thread continues, as if the faulting instruction had
+// called it.
+extern "C" fn raise(number: u32, srr0: u32, srr1: u32, dar: u32, dsisr: u32) -> ! {
+    let exception = match number {
+        n if n == ogc_sys::EX_DSI as u32 => Exception::DataStorage,
+        n if n == ogc_sys::EX_ISI as u32 => Exception::InstructionStorage,
+        n if n == ogc_sys::EX_ALIGN as u32 => Exception::Alignment,
+        _ if srr1 & SRR1_FP != 0 => Exception::FloatingPoint,
+        _ => Exception::Program,
+    };
+
+    // The first frame is the call site in this function, and the one after
+    // it the faulting instruction, through the return address `handle` made
+    // up for us.
+    // Nothing is allocated before the walk, in case the fault came from
+    // within the allocator. One slot is kept free for the faulting
+    // instruction in case the walk misses it.
+    let mut buf = [0; MAX_FRAMES + 1];
+    let mut len = 0;
+    backtrace::trace(|addr| {
+        buf[len + 1] = addr;
+        len += 1;
+        len < MAX_FRAMES
+    });
+    let frames = match buf[1..=len].iter().position(|&addr| addr == srr0 as usize) {
+        Some(i) => &buf[i + 1..=len],
+        None => {
+            buf[0] = srr0 as usize;
+            &buf[..=len]
+        }
+    };
+
+    let fault = Fault { exception, srr0, srr1, dar, dsisr, frames: frames.to_vec() };
+    let description = fault.to_string();
+
+    unsafe {
+        // The handler may well panic, so it must not leave the lock behind.
+        struct Unlock;
+        impl Drop for Unlock {
+            fn drop(&mut self) {
+                unsafe { HANDLER_LOCK.read_unlock() }
+            }
+        }
+
+        HANDLER_LOCK.read();
+        let unlock = Unlock;
+        match HANDLER {
+            Handler::Default => {
+                drop(unlock);
+                default_handler(fault);
+            }
+            Handler::Custom(ptr) => (*ptr)(fault),
+        }
+    }
+
+    rtabort!("exception handler returned after {}", description)
+}
@@ -2,7 +2,7 @@
 
 #![stable(feature = "rust1", since = "1.0.0")]
 
-//use crate::fs;
+use crate::fs;
 use crate::os::raw;
 use crate::sys;
 use crate::io;
@@ -62,26 +62,26 @@ pub trait IntoRawFd {
     fn into_raw_fd(self) -> RawFd;
 }
 
-// #[stable(feature = "rust1", since = "1.0.0")]
-// impl AsRawFd for fs::File {
-//     fn as_raw_fd(&self) -> RawFd {
-//         self.as_inner().fd().raw()
-//     }
-// }
+#[stable(feature = "rust1", since = "1.0.0")]
+impl AsRawFd for fs::File {
+    fn as_raw_fd(&self) -> RawFd {
+        self.as_inner().fd().raw()
+    }
+}
 
-// #[stable(feature = "from_raw_os", since = "1.1.0")]
-// impl FromRawFd for fs::File {
-//     unsafe fn from_raw_fd(fd: RawFd) -> fs::File {
-//         fs::File::from_inner(sys::fs::File::from_inner(fd))
-//     }
-// }
+#[stable(feature = "from_raw_os", since = "1.1.0")]
+impl FromRawFd for fs::File {
+    unsafe fn from_raw_fd(fd: RawFd) -> fs::File {
+        fs::File::from_inner(sys::fs::File::from_inner(fd))
+    }
+}
 
-// #[stable(feature = "into_raw_os", since = "1.4.0")]
-// impl IntoRawFd for fs::File {
-//     fn into_raw_fd(self) -> RawFd {
-//         self.into_inner().into_fd().into_raw()
-//     }
-// }
+#[stable(feature = "into_raw_os", since = "1.4.0")]
+impl IntoRawFd for fs::File {
+    fn into_raw_fd(self) -> RawFd {
+        self.into_inner().into_fd().into_raw()
+    }
+}
 
 #[stable(feature = "asraw_stdio", since = "1.21.0")]
 impl AsRawFd for io::Stdin {
@@ -2,30 +2,46 @@
 
 #![stable(feature = "thread_extensions", since = "1.9.0")]
 
-#[allow(deprecated)]
 use crate::sys_common::{AsInner, IntoInner};
 use crate::thread::JoinHandle;
 
+/// The raw LWP thread handle, usable with the `LWP_*` functions of libogc.
 #[stable(feature = "thread_extensions", since = "1.9.0")]
 #[allow(deprecated)]
-pub type RawPthread = libc::pthread_t;
+pub type RawPthread = ogc_sys::lwp_t;
 
 /// Unix-specific extensions to [`thread::JoinHandle`].
 ///
 /// [`thread::JoinHandle`]: ../../../../std/thread/struct.JoinHandle.html
 #[stable(feature = "thread_extensions", since = "1.9.0")]
 pub trait JoinHandleExt {
-    /// Extracts the raw pthread_t without taking ownership
+    /// Extracts the raw `lwp_t` without taking ownership
     #[stable(feature = "thread_extensions", since = "1.9.0")]
     fn as_pthread_t(&self) -> RawPthread;
 
-    /// Consumes the thread, returning the raw pthread_t
+    /// Consumes the thread, returning the raw `lwp_t`
     ///
-    /// This function **transfers ownership** of the underlying pthread_t to
-    /// the caller. Callers are then the unique owners of the pthread_t and
-    /// must either detach or join the pthread_t once it's no longer needed.
+    /// This function **transfers ownership** of the underlying `lwp_t` to
+    /// the caller. Callers are then the unique owners of the `lwp_t` and
+    /// must either join it with `LWP_JoinThread` or stop using it once the
+    /// thread may have finished, as libogc reuses the handles of finished
+    /// threads.
     #[stable(feature = "thread_extensions", since = "1.9.0")]
     fn into_pthread_t(self) -> RawPthread;
+
+    /// Changes the LWP priority of the thread with `LWP_SetThreadPriority`.
+    ///
+    /// Does nothing once the thread has finished, since libogc may have
+    /// handed its `lwp_t` to another thread by then.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `priority` is above
+    /// [`os::rvl_ios::thread::MAX_PRIORITY`].
+    ///
+    /// [`os::rvl_ios::thread::MAX_PRIORITY`]: ../../../../std/os/rvl_ios/thread/constant.MAX_PRIORITY.html
+    #[unstable(feature = "rvl_ios_thread_ext", issue = "0")]
+    fn set_priority(&self, priority: u8);
 }
 
 #[stable(feature = "thread_extensions", since = "1.9.0")]
@@ -37,4 +53,8 @@ impl<T> JoinHandleExt for JoinHandle<T> {
     fn into_pthread_t(self) -> RawPthread {
         self.into_inner().into_id() as RawPthread
     }
+
+    fn set_priority(&self, priority: u8) {
+        self.as_inner().set_priority(priority)
+    }
 }
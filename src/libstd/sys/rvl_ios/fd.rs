@@ -3,12 +3,17 @@
 use crate::cmp;
 use crate::io::{self, Read, Initializer};
 use crate::mem;
-use crate::sync::atomic::{AtomicBool, Ordering};
 use crate::sys::cvt;
 use crate::sys_common::AsInner;
 
 use libc::{c_int, c_void, ssize_t};
 
+/// A newlib file descriptor.
+///
+/// These come out of the devoptab layer (stdio, `sd:/`, `usb:/`, ...) and
+/// are only meaningful to newlib. Sockets created by the IOS network stack
+/// use their own descriptor table and are wrapped by
+/// `sys::net::SocketDesc` instead.
 #[derive(Debug)]
 pub struct FileDesc {
     fd: c_int,
@@ -46,9 +51,9 @@ impl FileDesc {
 
     pub fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
         let ret = cvt(unsafe {
-            ogc_sys::net_read(self.fd,
+            libc::read(self.fd,
                        buf.as_mut_ptr() as *mut c_void,
-                       cmp::min(buf.len() as i32, max_len() as i32))
+                       cmp::min(buf.len(), max_len()))
         })?;
         Ok(ret as usize)
     }
@@ -108,9 +113,9 @@ impl FileDesc {
 
     pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
         let ret = cvt(unsafe {
-            ogc_sys::net_write(self.fd,
+            libc::write(self.fd,
                         buf.as_ptr() as *const c_void,
-                        cmp::min(buf.len() as i32, max_len() as i32))
+                        cmp::min(buf.len(), max_len()))
         })?;
         Ok(ret as usize)
     }
@@ -163,87 +168,26 @@ impl FileDesc {
         }
     }
 
-    pub fn get_cloexec(&self) -> io::Result<bool> {
-        unsafe {
-            Ok((cvt(ogc_sys::net_fcntl(self.fd, libc::F_GETFD as u32, 0))? & libc::FD_CLOEXEC) != 0)
-        }
-    }
-
-    pub fn set_cloexec(&self) -> io::Result<()> {
-        unsafe {
-            let previous = cvt(ogc_sys::net_fcntl(self.fd, libc::F_GETFD as u32, 0))?;
-            let new = previous | libc::FD_CLOEXEC;
-            if new != previous {
-                cvt(ogc_sys::net_fcntl(self.fd, libc::F_SETFD as u32, new as u32))?;
-            }
-            Ok(())
-        }
-    }
-
-    
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
         unsafe {
-            let previous = cvt(ogc_sys::net_fcntl(self.fd, libc::F_GETFL as u32, 0))?;
+            let previous = cvt(libc::fcntl(self.fd, libc::F_GETFL))?;
             let new = if nonblocking {
                 previous | libc::O_NONBLOCK
             } else {
                 previous & !libc::O_NONBLOCK
             };
             if new != previous {
-                cvt(ogc_sys::net_fcntl(self.fd, libc::F_SETFL as u32, new as u32))?;
+                cvt(libc::fcntl(self.fd, libc::F_SETFL, new))?;
             }
             Ok(())
         }
     }
 
     pub fn duplicate(&self) -> io::Result<FileDesc> {
-        // We want to atomically duplicate this file descriptor and set the
-        // CLOEXEC flag, and currently that's done via F_DUPFD_CLOEXEC. This
-        // flag, however, isn't supported on older Linux kernels (earlier than
-        // 2.6.24).
-        //
-        // To detect this and ensure that CLOEXEC is still set, we
-        // follow a strategy similar to musl [1] where if passing
-        // F_DUPFD_CLOEXEC causes `fcntl` to return EINVAL it means it's not
-        // supported (the third parameter, 0, is always valid), so we stop
-        // trying that.
-        //
-        // Also note that Android doesn't have F_DUPFD_CLOEXEC, but get it to
-        // resolve so we at least compile this.
-        //
-        // [1]: http://comments.gmane.org/gmane.linux.lib.musl.general/2963
-        #[cfg(any(target_os = "android", target_os = "haiku"))]
-        use libc::F_DUPFD as F_DUPFD_CLOEXEC;
-        #[cfg(not(any(target_os = "android", target_os="haiku")))]
-        use libc::F_DUPFD_CLOEXEC;
-
-        let make_filedesc = |fd| {
-            let fd = FileDesc::new(fd);
-            fd.set_cloexec()?;
-            Ok(fd)
-        };
-        static TRY_CLOEXEC: AtomicBool =
-            AtomicBool::new(!cfg!(target_os = "android"));
-        let fd = self.raw();
-        if TRY_CLOEXEC.load(Ordering::Relaxed) {
-            match cvt(unsafe { libc::fcntl(fd, F_DUPFD_CLOEXEC, 0) }) {
-                // We *still* call the `set_cloexec` method as apparently some
-                // linux kernel at some point stopped setting CLOEXEC even
-                // though it reported doing so on F_DUPFD_CLOEXEC.
-                Ok(fd) => {
-                    return Ok(if cfg!(target_os = "linux") {
-                        make_filedesc(fd)?
-                    } else {
-                        FileDesc::new(fd)
-                    })
-                }
-                Err(ref e) if e.raw_os_error() == Some(libc::EINVAL) => {
-                    TRY_CLOEXEC.store(false, Ordering::Relaxed);
-                }
-                Err(e) => return Err(e),
-            }
-        }
-        cvt(unsafe { libc::fcntl(fd, libc::F_DUPFD, 0) }).and_then(make_filedesc)
+        // devoptab descriptors have no equivalent of dup(2), and there is no
+        // exec to hide them from anyway.
+        Err(io::Error::new(io::ErrorKind::Other,
+                           "duplicating file descriptors is not supported on this platform"))
     }
 }
 
@@ -269,6 +213,6 @@ impl Drop for FileDesc {
         // the file descriptor was closed or not, and if we retried (for
         // something like EINTR), we might close another valid file descriptor
         // opened after we closed ours.
-        let _ = unsafe { ogc_sys::net_close(self.fd) };
+        let _ = unsafe { libc::close(self.fd) };
     }
 }
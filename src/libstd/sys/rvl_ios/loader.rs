@@ -0,0 +1,508 @@
+//! Leaving the running program: returning to whatever started it, resetting
+//! or powering off the console, or replacing the program with another DOL or
+//! ELF executable.
+
+use crate::alloc::Layout;
+use crate::env;
+use crate::ffi::OsStr;
+use crate::fs::File;
+use crate::io::{self, Read};
+use crate::mem;
+use crate::path::Path;
+use crate::ptr;
+use crate::slice;
+use crate::sys::arena;
+use crate::sys::ext::ffi::OsStrExt;
+
+// The Homebrew Channel, and most loaders modelled after it, leave a small
+// stub behind at this address that reloads them. It is tagged with
+// "STUBHAXX" right after its first instruction.
+const LOADER_STUB: usize = 0x8000_1800;
+
+// Cached MEM1, the only place executables are loaded to.
+const MEM1_START: u32 = 0x8000_0000;
+const MEM1_END: u32 = 0x8180_0000;
+
+const EM_PPC: u16 = 20;
+const PT_LOAD: u32 = 1;
+
+fn loader_stub_present() -> bool {
+    unsafe {
+        ptr::read_volatile((LOADER_STUB + 4) as *const u32) == u32::from_be_bytes(*b"STUB")
+            && ptr::read_volatile((LOADER_STUB + 8) as *const u32) == u32::from_be_bytes(*b"HAXX")
+    }
+}
+
+fn reset_system(kind: u32) -> ! {
+    unsafe {
+        ogc_sys::SYS_ResetSystem(kind as i32, 0, 0);
+        // Only `SYS_SHUTDOWN` comes back, which is never passed here.
+        crate::sys::abort_internal()
+    }
+}
+
+// Shuts libogc down and jumps to `entry` with interrupts and exception
+// handlers out of the way, the state a freshly loaded program expects.
+unsafe fn shutdown_and_jump(entry: usize, arg0: usize, arg1: usize) -> ! {
+    ogc_sys::SYS_ResetSystem(ogc_sys::SYS_SHUTDOWN as i32, 0, 0);
+    ogc_sys::IRQ_Disable();
+    ogc_sys::__exception_closeall();
+    let entry: extern "C" fn(usize, usize) -> ! = mem::transmute(entry);
+    entry(arg0, arg1)
+}
+
+pub fn return_to_loader() -> ! {
+    if !loader_stub_present() {
+        return_to_menu()
+    }
+    unsafe { shutdown_and_jump(LOADER_STUB, 0, 0) }
+}
+
+pub fn return_to_menu() -> ! {
+    reset_system(ogc_sys::SYS_RETURNTOMENU)
+}
+
+pub fn reset() -> ! {
+    reset_system(ogc_sys::SYS_HOTRESET)
+}
+
+pub fn power_off() -> ! {
+    reset_system(ogc_sys::SYS_POWEROFF)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Chainloading
+////////////////////////////////////////////////////////////////////////////////
+
+// The new image is loaded right where the running one lives, so the final
+// copy can't be done by Rust code. Instead everything is staged in MEM2 and
+// this stub, copied to MEM2 as well, moves it into place and jumps to the
+// entry point. It takes a table of `[dst, src, len]` entries in r3, where a
+// `src` of 0 means zeroing the range, terminated by a `dst` of 0, and the
+// entry point in r4. Only relative branches are used, so it runs from
+// wherever it is copied to.
+global_asm!(r#"
+    .section .text.__rvl_ios_chainload_stub,"ax",@progbits
+    .globl __rvl_ios_chainload_stub
+    .globl __rvl_ios_chainload_stub_end
+    .p2align 5
+__rvl_ios_chainload_stub:
+1:  lwz     5, 0(3)
+    cmpwi   5, 0
+    beq     5f
+    lwz     6, 4(3)
+    lwz     7, 8(3)
+    addi    3, 3, 12
+    li      8, 0
+2:  li      9, 0
+    cmpwi   6, 0
+    beq     3f
+    lbzx    9, 6, 8
+3:  stbx    9, 5, 8
+    addi    8, 8, 1
+    cmplw   8, 7
+    blt     2b
+    addi    7, 7, 31
+    li      8, 0
+4:  dcbst   5, 8
+    sync
+    icbi    5, 8
+    addi    8, 8, 32
+    cmplw   8, 7
+    blt     4b
+    b       1b
+5:  sync
+    isync
+    mtctr   4
+    bctr
+__rvl_ios_chainload_stub_end:
+"#);
+
+extern "C" {
+    static __rvl_ios_chainload_stub: u8;
+    static __rvl_ios_chainload_stub_end: u8;
+}
+
+// A range of the new image's memory: `len` bytes at `dst`.
+struct Segment {
+    dst: u32,
+    src: Source,
+    len: u32,
+}
+
+enum Source {
+    // An offset into the executable.
+    File(usize),
+    // Zero-filled, like .bss.
+    Zero,
+    // A staged buffer at this address.
+    Staged(u32),
+}
+
+// A buffer in MEM2, which the new image can't be loaded over. It comes from
+// the same arena as `Mem2Alloc`'s allocations, see `chainload`.
+struct Staged {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+impl Staged {
+    fn new(len: usize) -> io::Result<Staged> {
+        let layout = Layout::from_size_align(len.max(1), 32).map_err(|_| too_large())?;
+        let ptr = unsafe { arena::MEM2.alloc(layout) };
+        if ptr.is_null() {
+            return Err(io::Error::new(io::ErrorKind::Other,
+                                      "not enough MEM2 left to stage the image"));
+        }
+        Ok(Staged { ptr, layout })
+    }
+
+    fn from_slice(data: &[u8]) -> io::Result<Staged> {
+        let staged = Staged::new(data.len())?;
+        unsafe { ptr::copy_nonoverlapping(data.as_ptr(), staged.ptr, data.len()) };
+        Ok(staged)
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr, self.layout.size()) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.layout.size()) }
+    }
+
+    fn addr(&self) -> u32 {
+        self.ptr as u32
+    }
+}
+
+impl Drop for Staged {
+    fn drop(&mut self) {
+        unsafe { arena::MEM2.dealloc(self.ptr, self.layout) }
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn too_large() -> io::Error {
+    invalid("the image is too large")
+}
+
+fn be_u16(image: &[u8], offset: usize) -> io::Result<u16> {
+    let b = image.get(offset..).and_then(|b| b.get(..2)).ok_or_else(|| invalid("truncated executable"))?;
+    Ok(u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn be_u32(image: &[u8], offset: usize) -> io::Result<u32> {
+    let b = image.get(offset..).and_then(|b| b.get(..4)).ok_or_else(|| invalid("truncated executable"))?;
+    Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn check_file_range(image: &[u8], offset: u32, len: u32) -> io::Result<()> {
+    match (offset as usize).checked_add(len as usize) {
+        Some(end) if end <= image.len() => Ok(()),
+        _ => Err(invalid("truncated executable")),
+    }
+}
+
+fn parse_dol(image: &[u8]) -> io::Result<(Vec<Segment>, u32)> {
+    // 7 text sections followed by 11 data sections, described by parallel
+    // tables of file offsets, load addresses and sizes. crt0 clears .bss on
+    // its own.
+    let mut segments = Vec::new();
+    for i in 0..18 {
+        let offset = be_u32(image, i * 4)?;
+        let dst = be_u32(image, 0x48 + i * 4)?;
+        let len = be_u32(image, 0x90 + i * 4)?;
+        if len == 0 {
+            continue;
+        }
+        check_file_range(image, offset, len)?;
+        segments.push(Segment { dst, src: Source::File(offset as usize), len });
+    }
+    Ok((segments, be_u32(image, 0xe0)?))
+}
+
+fn parse_elf(image: &[u8]) -> io::Result<(Vec<Segment>, u32)> {
+    if image.get(4) != Some(&1) || image.get(5) != Some(&2) || be_u16(image, 0x12)? != EM_PPC {
+        return Err(invalid("not a 32-bit big endian PowerPC ELF"));
+    }
+    let entry = be_u32(image, 0x18)?;
+    let phoff = be_u32(image, 0x1c)? as usize;
+    let phentsize = be_u16(image, 0x2a)? as usize;
+    let phnum = be_u16(image, 0x2c)? as usize;
+    let mut segments = Vec::new();
+    for i in 0..phnum {
+        let ph = phoff.checked_add(i * phentsize)
+            .ok_or_else(|| invalid("invalid program header"))?;
+        if be_u32(image, ph)? != PT_LOAD {
+            continue;
+        }
+        let ph = &image[ph..];
+        let offset = be_u32(ph, 0x04)?;
+        let dst = be_u32(ph, 0x08)?;
+        let filesz = be_u32(ph, 0x10)?;
+        let memsz = be_u32(ph, 0x14)?;
+        if filesz > 0 {
+            check_file_range(image, offset, filesz)?;
+            segments.push(Segment { dst, src: Source::File(offset as usize), len: filesz });
+        }
+        if memsz > filesz {
+            let dst = dst.checked_add(filesz).ok_or_else(|| invalid("invalid program header"))?;
+            segments.push(Segment { dst, src: Source::Zero, len: memsz - filesz });
+        }
+    }
+    Ok((segments, entry))
+}
+
+// Reads the word the image will have at `addr`, if one of its segments
+// covers it.
+fn peek(image: &[u8], segments: &[Segment], addr: u32) -> Option<u32> {
+    segments.iter().find_map(|s| match s.src {
+        Source::File(offset) if s.dst <= addr && addr - s.dst + 4 <= s.len => {
+            be_u32(image, offset + (addr - s.dst) as usize).ok()
+        }
+        _ => None,
+    })
+}
+
+// The command line `sys::args` parses on the other side: the path of the
+// executable and every argument, each terminated by a NUL, with one more NUL
+// at the end.
+fn command_line(path: &Path, args: &[&OsStr]) -> Vec<u8> {
+    let mut line = Vec::new();
+    for arg in Some(path.as_os_str()).into_iter().chain(args.iter().cloned()) {
+        line.extend_from_slice(arg.as_bytes());
+        line.push(0);
+    }
+    line.push(0);
+    line
+}
+
+pub fn chainload(path: &Path, args: &[&OsStr]) -> io::Error {
+    match boot(path, args) {
+        Ok(never) => never,
+        Err(e) => {
+            // Staging may have been what made `Mem2Alloc` take memory from
+            // MEM2's arena, which mustn't keep `Mem2Alloc::reserve` from
+            // working later on.
+            arena::MEM2.release();
+            e
+        }
+    }
+}
+
+fn boot(path: &Path, args: &[&OsStr]) -> io::Result<!> {
+    let path = if path.is_absolute() { path.to_path_buf() } else { env::current_dir()?.join(path) };
+
+    let mut file = File::open(&path)?;
+    let len = file.metadata()?.len();
+    if len > u32::max_value() as u64 {
+        return Err(too_large());
+    }
+    let mut image = Staged::new(len as usize)?;
+    file.read_exact(&mut image.as_mut_slice()[..len as usize])?;
+    drop(file);
+
+    let data = &image.as_slice()[..len as usize];
+    let (mut segments, entry) = if data.starts_with(b"\x7fELF") {
+        parse_elf(data)?
+    } else {
+        parse_dol(data)?
+    };
+    for segment in segments.iter_mut() {
+        // ELFs commonly give physical addresses.
+        if segment.dst < MEM1_START {
+            segment.dst |= MEM1_START;
+        }
+        match segment.dst.checked_add(segment.len) {
+            Some(end) if segment.dst >= MEM1_START && end <= MEM1_END => {}
+            _ => return Err(invalid("the image doesn't load into MEM1")),
+        }
+    }
+    let entry = entry | MEM1_START;
+
+    // Images built with libogc's crt0 reserve space for the arguments right
+    // behind the entry point, tagged with ARGV_MAGIC. Others get none.
+    let line = command_line(&path, args);
+    let staged_line = Staged::from_slice(&line)?;
+    let argv_block = ogc_sys::__argv {
+        argvMagic: ogc_sys::ARGV_MAGIC as i32,
+        commandLine: staged_line.ptr as *mut _,
+        length: line.len() as i32,
+        argc: 0,
+        argv: ptr::null_mut(),
+        endARGV: ptr::null_mut(),
+    };
+    let argv = Staged::from_slice(unsafe {
+        slice::from_raw_parts(&argv_block as *const _ as *const u8,
+                              mem::size_of::<ogc_sys::__argv>())
+    })?;
+    if peek(data, &segments, entry + 4) == Some(ogc_sys::ARGV_MAGIC) {
+        segments.push(Segment {
+            dst: entry + 8,
+            src: Source::Staged(argv.addr()),
+            len: mem::size_of::<ogc_sys::__argv>() as u32,
+        });
+    }
+
+    let mut table = Vec::with_capacity(segments.len() + 1);
+    for segment in &segments {
+        let src = match segment.src {
+            Source::File(offset) => image.addr() + offset as u32,
+            Source::Zero => 0,
+            Source::Staged(addr) => addr,
+        };
+        table.push([segment.dst, src, segment.len]);
+    }
+    table.push([0, 0, 0]);
+    let table = Staged::from_slice(unsafe {
+        slice::from_raw_parts(table.as_ptr() as *const u8, table.len() * 12)
+    })?;
+
+    unsafe {
+        let start = &__rvl_ios_chainload_stub as *const u8;
+        let end = &__rvl_ios_chainload_stub_end as *const u8;
+        let stub = Staged::from_slice(slice::from_raw_parts(start, end as usize - start as usize))?;
+        ogc_sys::DCFlushRange(stub.ptr as *mut _, stub.layout.size() as u32);
+        ogc_sys::ICInvalidateRange(stub.ptr as *mut _, stub.layout.size() as u32);
+
+        let entry_point = stub.ptr as usize;
+        let table_addr = table.ptr as usize;
+        // Everything staged has to stay where it is.
+        mem::forget((image, staged_line, argv, table, stub));
+        shutdown_and_jump(entry_point, table_addr, entry as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::OsString;
+    use crate::io::ErrorKind;
+    use crate::sys::args;
+
+    fn put_u32(buf: &mut [u8], offset: usize, value: u32) {
+        buf[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_u16(buf: &mut [u8], offset: usize, value: u16) {
+        buf[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+    }
+
+    // An ELF header followed by `phnum` program headers at `phoff`, each
+    // given as (offset, vaddr, filesz, memsz), and `data` at 0x100.
+    fn elf(phoff: u32, phnum: u16, headers: &[(u32, u32, u32, u32)], data: &[u8]) -> Vec<u8> {
+        let mut image = vec![0; 0x100 + data.len()];
+        image[..6].copy_from_slice(b"\x7fELF\x01\x02");
+        put_u16(&mut image, 0x12, EM_PPC);
+        put_u32(&mut image, 0x18, 0x8000_4000);
+        put_u32(&mut image, 0x1c, phoff);
+        put_u16(&mut image, 0x2a, 32);
+        put_u16(&mut image, 0x2c, phnum);
+        for (i, &(offset, vaddr, filesz, memsz)) in headers.iter().enumerate() {
+            let ph = 0x34 + i * 32;
+            put_u32(&mut image, ph, PT_LOAD);
+            put_u32(&mut image, ph + 0x04, offset);
+            put_u32(&mut image, ph + 0x08, vaddr);
+            put_u32(&mut image, ph + 0x10, filesz);
+            put_u32(&mut image, ph + 0x14, memsz);
+        }
+        image[0x100..].copy_from_slice(data);
+        image
+    }
+
+    fn assert_invalid<T>(result: io::Result<T>) {
+        match result {
+            Ok(_) => panic!("accepted an invalid executable"),
+            Err(e) => assert_eq!(e.kind(), ErrorKind::InvalidData),
+        }
+    }
+
+    #[test]
+    fn dol() {
+        let mut image = vec![0; 0x110];
+        put_u32(&mut image, 0x00, 0x100);
+        put_u32(&mut image, 0x48, 0x8000_3100);
+        put_u32(&mut image, 0x90, 0x10);
+        put_u32(&mut image, 0xe0, 0x8000_3100);
+        put_u32(&mut image, 0x104, 0x1234_5678);
+
+        let (segments, entry) = parse_dol(&image).unwrap();
+        assert_eq!(entry, 0x8000_3100);
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].dst, segments[0].len), (0x8000_3100, 0x10));
+        match segments[0].src {
+            Source::File(offset) => assert_eq!(offset, 0x100),
+            _ => panic!("expected a file segment"),
+        }
+        assert_eq!(peek(&image, &segments, 0x8000_3104), Some(0x1234_5678));
+        assert_eq!(peek(&image, &segments, 0x8000_310e), None);
+    }
+
+    #[test]
+    fn truncated_dol() {
+        assert_invalid(parse_dol(&[0; 0x40]));
+        assert_invalid(parse_dol(&[0; 0xe0]));
+
+        // A section reaching past the end of the file.
+        let mut image = vec![0; 0x108];
+        put_u32(&mut image, 0x00, 0x100);
+        put_u32(&mut image, 0x48, 0x8000_3100);
+        put_u32(&mut image, 0x90, 0x10);
+        assert_invalid(parse_dol(&image));
+    }
+
+    #[test]
+    fn truncated_elf() {
+        let image = elf(0x34, 1, &[(0x100, 0x8000_4000, 8, 8)], &[0; 8]);
+        assert!(parse_elf(&image).is_ok());
+        assert_invalid(parse_elf(&image[..0x20]));
+        assert_invalid(parse_elf(&image[..0x40]));
+        // The segment's data is cut off.
+        assert_invalid(parse_elf(&image[..0x104]));
+    }
+
+    #[test]
+    fn overflowing_phoff() {
+        assert_invalid(parse_elf(&elf(0xffff_fff0, 2, &[], &[])));
+        assert_invalid(parse_elf(&elf(0x34, 0xffff, &[], &[])));
+    }
+
+    #[test]
+    fn elf_bss() {
+        let data = [0xde, 0xad, 0xbe, 0xef, 0x01, 0x02, 0x03, 0x04];
+        let image = elf(0x34, 1, &[(0x100, 0x8000_4000, 8, 0x20)], &data);
+        let (segments, entry) = parse_elf(&image).unwrap();
+        assert_eq!(entry, 0x8000_4000);
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].dst, segments[0].len), (0x8000_4000, 8));
+        match segments[0].src {
+            Source::File(offset) => assert_eq!(offset, 0x100),
+            _ => panic!("expected a file segment"),
+        }
+        assert_eq!((segments[1].dst, segments[1].len), (0x8000_4008, 0x18));
+        match segments[1].src {
+            Source::Zero => {}
+            _ => panic!("expected a zero-filled segment"),
+        }
+        assert_eq!(peek(&image, &segments, 0x8000_4004), Some(0x0102_0304));
+        assert_eq!(peek(&image, &segments, 0x8000_4008), None);
+    }
+
+    #[test]
+    fn command_line_round_trip() {
+        let path = Path::new("sd:/apps/emulator/boot.dol");
+        let args = [OsStr::new("sd:/roms/game.nes"), OsStr::new(""), OsStr::new("--fast start")];
+        let line = command_line(path, &args);
+        assert!(line.ends_with(b"start\0\0"));
+        let parsed = args::parse_command_line(&line);
+        let expected: Vec<OsString> = ["sd:/apps/emulator/boot.dol", "sd:/roms/game.nes", "--fast start"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert_eq!(parsed, expected);
+    }
+}
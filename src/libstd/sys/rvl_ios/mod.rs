@@ -12,17 +12,21 @@ pub use libc::strlen;
 pub mod weak;
 
 pub mod alloc;
+pub mod arena;
 pub mod args;
+pub mod backtrace;
 //pub mod android;
 pub mod cmath;
 pub mod condvar;
 pub mod env;
+pub mod exception;
 pub mod ext;
 pub mod fast_thread_local;
 pub mod fd;
-//pub mod fs;
+pub mod fs;
 pub mod memchr;
 pub mod io;
+pub mod loader;
 pub mod mutex;
 #[cfg(not(target_os = "l4re"))]
 pub mod net;
@@ -32,10 +36,10 @@ mod l4re;
 pub use self::l4re::net;
 pub mod os;
 pub mod path;
-//pub mod pipe;
-//pub mod process;
+pub mod pipe;
+pub mod process;
 pub mod rand;
-//pub mod rwlock;
+pub mod rwlock;
 pub mod stack_overflow;
 pub mod thread;
 pub mod thread_local;
@@ -57,6 +61,12 @@ pub fn init() {
         reset_sigpipe();
     }
 
+    // Let CPU exceptions reach the panic machinery instead of halting the
+    // console in libogc's handler.
+    unsafe {
+        exception::init();
+    }
+
     #[cfg(not(any(target_os = "emscripten", target_os = "fuchsia")))]
     unsafe fn reset_sigpipe() {
         assert!(signal(ogc_sys::SIGPIPE as i32, libc::SIG_IGN) != libc::SIG_ERR);
@@ -96,6 +106,20 @@ pub fn decode_error_kind(errno: i32) -> ErrorKind {
     }
 }
 
+// There are no child processes, so the types `std::process` expects for them
+// are uninhabited.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Void {}
+
+pub fn unsupported<T>() -> crate::io::Result<T> {
+    Err(unsupported_err())
+}
+
+pub fn unsupported_err() -> crate::io::Error {
+    crate::io::Error::new(ErrorKind::Other,
+                          "operation not supported on rvl-ios")
+}
+
 #[doc(hidden)]
 pub trait IsMinusOne {
     fn is_minus_one(&self) -> bool;
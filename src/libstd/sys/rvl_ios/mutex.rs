@@ -1,15 +1,27 @@
 use crate::cell::UnsafeCell;
 use crate::mem;
+use crate::ptr;
 
 pub struct Mutex {
-    inner: UnsafeCell<libc::pthread_mutex_t>,
+    inner: UnsafeCell<ogc_sys::mutex_t>,
 }
 
+// The handle libogc uses for "no mutex" (`LWP_MUTEX_NULL`).
+const LWP_MUTEX_NULL: ogc_sys::mutex_t = 0xffff_ffff;
+
 #[inline]
-pub unsafe fn raw(m: &Mutex) -> *mut libc::pthread_mutex_t {
+pub unsafe fn raw(m: &Mutex) -> *mut ogc_sys::mutex_t {
     m.inner.get()
 }
 
+// `LWP_MutexInit` stores a `mutex_t` handle in the cell; every other libogc
+// call takes that handle by value rather than a pointer to it.
+#[inline]
+pub unsafe fn handle(m: &Mutex) -> ogc_sys::mutex_t {
+    m.ensure_init();
+    *m.inner.get()
+}
+
 unsafe impl Send for Mutex {}
 unsafe impl Sync for Mutex {}
 
@@ -20,7 +32,25 @@ impl Mutex {
         // initialization of potentially opaque OS data before it landed.
         // Be very careful using this newly constructed `Mutex`, reentrant
         // locking is undefined behavior until `init` is called!
-        Mutex { inner: UnsafeCell::new(libc::PTHREAD_MUTEX_INITIALIZER) }
+        Mutex { inner: UnsafeCell::new(LWP_MUTEX_NULL) }
+    }
+
+    // Unlike a pthread mutex there is no static initializer for an LWP
+    // mutex, and statics never get `init` called on them (see e.g.
+    // `sys_common::at_exit_imp`), so those are created on first use.
+    #[inline]
+    unsafe fn ensure_init(&self) {
+        if ptr::read_volatile(self.inner.get()) != LWP_MUTEX_NULL {
+            return;
+        }
+        // Broadway only has a single core, so with interrupts disabled nobody
+        // can get between the check and the initialization below.
+        let level = ogc_sys::IRQ_Disable();
+        if *self.inner.get() == LWP_MUTEX_NULL {
+            let r = ogc_sys::LWP_MutexInit(self.inner.get(), false);
+            debug_assert_eq!(r, 0);
+        }
+        ogc_sys::IRQ_Restore(level);
     }
 
     #[inline]
@@ -54,34 +84,38 @@ impl Mutex {
 
         // Note: libogc doesnt require special init functions as seen above.
         // The LWP_MutexInit function uses a boolean to determine if it will be recursive or not.
-        let r = ogc_sys::LWP_MutexInit(self.inner.get() as *mut u32, false);
+        let r = ogc_sys::LWP_MutexInit(self.inner.get(), false);
         debug_assert_eq!(r, 0);
     }
 
     #[inline]
     pub unsafe fn lock(&self) {
         //let r = libc::pthread_mutex_lock(self.inner.get());
-        let r = ogc_sys::LWP_MutexLock(self.inner.get() as u32);
+        let r = ogc_sys::LWP_MutexLock(handle(self));
         debug_assert_eq!(r, 0);
     }
 
     #[inline]
     pub unsafe fn unlock(&self) {
         //let r = libc::pthread_mutex_unlock(self.inner.get());
-        let r = ogc_sys::LWP_MutexUnlock(self.inner.get() as u32);
+        let r = ogc_sys::LWP_MutexUnlock(handle(self));
         debug_assert_eq!(r, 0);
     }
 
     #[inline]
     pub unsafe fn try_lock(&self) -> bool {
         //libc::pthread_mutex_trylock(self.inner.get()) == 0
-        ogc_sys::LWP_MutexTryLock(self.inner.get() as u32) == 0
+        ogc_sys::LWP_MutexTryLock(handle(self)) == 0
     }
 
     #[inline]
     pub unsafe fn destroy(&self) {
+        // A static mutex that was never locked has nothing to destroy.
+        if *self.inner.get() == LWP_MUTEX_NULL {
+            return;
+        }
         //let r = libc::pthread_mutex_destroy(self.inner.get());
-        let r = ogc_sys::LWP_MutexDestroy(self.inner.get() as u32);
+        let r = ogc_sys::LWP_MutexDestroy(*self.inner.get());
         debug_assert_eq!(r, 0);
     }
 }
@@ -116,25 +150,25 @@ impl ReentrantMutex {
 
     pub unsafe fn lock(&self) {
         //let result = libc::pthread_mutex_lock(self.inner.get());
-        let result = ogc_sys::LWP_MutexLock(self.inner.get() as u32);
+        let result = ogc_sys::LWP_MutexLock(*(self.inner.get() as *const ogc_sys::mutex_t));
         debug_assert_eq!(result, 0);
     }
 
     #[inline]
     pub unsafe fn try_lock(&self) -> bool {
         //libc::pthread_mutex_trylock(self.inner.get()) == 0
-        ogc_sys::LWP_MutexTryLock(self.inner.get() as u32) == 0
+        ogc_sys::LWP_MutexTryLock(*(self.inner.get() as *const ogc_sys::mutex_t)) == 0
     }
 
     pub unsafe fn unlock(&self) {
         //let result = libc::pthread_mutex_unlock(self.inner.get());
-        let result = ogc_sys::LWP_MutexUnlock(self.inner.get() as u32);
+        let result = ogc_sys::LWP_MutexUnlock(*(self.inner.get() as *const ogc_sys::mutex_t));
         debug_assert_eq!(result, 0);
     }
 
     pub unsafe fn destroy(&self) {
         //let result = libc::pthread_mutex_destroy(self.inner.get());
-        let result = ogc_sys::LWP_MutexDestroy(self.inner.get() as u32);
+        let result = ogc_sys::LWP_MutexDestroy(*(self.inner.get() as *const ogc_sys::mutex_t));
         debug_assert_eq!(result, 0);
     }
 }
@@ -1,46 +1,178 @@
 use crate::cmp;
-use crate::ffi::CStr;
+use crate::ffi::CString;
 use crate::io::{self, IoSlice, IoSliceMut};
 use crate::mem;
-use crate::net::{Shutdown, SocketAddr};
-use crate::str;
-use crate::sys::fd::FileDesc;
+use crate::net::{Ipv4Addr, Shutdown, SocketAddr};
+use crate::ptr;
+use crate::sys_common::mutex::Mutex;
 use crate::sys_common::net::{getsockopt, setsockopt, sockaddr_to_addr};
 use crate::sys_common::{AsInner, FromInner, IntoInner};
 use crate::time::{Duration, Instant};
 
-use libc::{c_int, c_void, size_t, sockaddr, socklen_t, MSG_PEEK};
+use libc::{c_int, c_void, sockaddr, socklen_t, MSG_PEEK};
 
 pub use crate::sys::{cvt, cvt_r};
 
 #[allow(unused_extern_crates)]
 pub extern crate libc as netc;
 
-pub type wrlen_t = size_t;
+// Lengths passed to `net_send`/`net_sendto`.
+pub type wrlen_t = i32;
 
 const SOCK_CLOEXEC: c_int = 0;
 const SO_NOSIGPIPE: c_int = 0;
 
-pub struct Socket(FileDesc);
+pub struct Socket(SocketDesc);
+
+// Descriptors handed out by `net_socket`/`net_accept` index the IOS network
+// stack's own table, which has nothing to do with newlib's descriptors (see
+// `sys::fd::FileDesc`). The same small integer can be valid in both, so a
+// socket must only ever be handed to the `net_*` functions.
+pub struct SocketDesc {
+    fd: c_int,
+}
+
+impl SocketDesc {
+    pub fn new(fd: c_int) -> SocketDesc {
+        SocketDesc { fd }
+    }
+
+    pub fn raw(&self) -> c_int { self.fd }
+
+    /// Extracts the actual socket descriptor without closing it.
+    pub fn into_raw(self) -> c_int {
+        let fd = self.fd;
+        mem::forget(self);
+        fd
+    }
+
+    pub fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let ret = cvt_net(unsafe {
+            ogc_sys::net_read(self.fd,
+                              buf.as_mut_ptr() as *mut c_void,
+                              cmp::min(buf.len(), c_int::max_value() as usize) as i32)
+        })?;
+        Ok(ret as usize)
+    }
+
+    pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        let ret = cvt_net(unsafe {
+            ogc_sys::net_write(self.fd,
+                               buf.as_ptr() as *const c_void,
+                               cmp::min(buf.len(), c_int::max_value() as usize) as i32)
+        })?;
+        Ok(ret as usize)
+    }
+}
+
+impl AsInner<c_int> for SocketDesc {
+    fn as_inner(&self) -> &c_int { &self.fd }
+}
+
+impl Drop for SocketDesc {
+    fn drop(&mut self) {
+        // Note that errors are ignored when closing a socket. The reason for
+        // this is that if an error occurs we don't actually know if the
+        // socket was closed or not, and if we retried we might close another
+        // valid socket opened after we closed ours.
+        let _ = unsafe { ogc_sys::net_close(self.fd) };
+    }
+}
 
 pub fn init() {}
 
-pub fn cvt_gai(err: c_int) -> io::Result<()> {
-    if err == 0 {
-        return Ok(());
+/// Checks the result of one of libogc's `net_*` functions.
+///
+/// Unlike their BSD counterparts these never touch `errno`; a failure is
+/// reported by returning the negated error code instead.
+pub fn cvt_net(t: i32) -> io::Result<i32> {
+    if t < 0 {
+        Err(io::Error::from_raw_os_error(-t))
+    } else {
+        Ok(t)
+    }
+}
+
+/// Like `cvt_net`, but retries the call for as long as it is interrupted.
+pub fn cvt_net_r<F>(mut f: F) -> io::Result<i32>
+    where F: FnMut() -> i32
+{
+    loop {
+        match cvt_net(f()) {
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            other => return other,
+        }
     }
+}
 
-    // if err == EAI_SYSTEM {
-    //     return Err(io::Error::last_os_error());
-    // }
+// IOS only resolves names through `net_gethostbyname`, which neither reports
+// a record's TTL nor can be called by two threads at once, since it returns a
+// pointer into a static buffer. Lookups are therefore serialized by `DNS_LOCK`,
+// and their results kept for a fixed `DNS_CACHE_TTL` in a small cache.
+const DNS_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+const DNS_CACHE_ENTRIES: usize = 16;
+
+struct DnsEntry {
+    host: String,
+    addrs: Vec<Ipv4Addr>,
+    expires: Instant,
+}
 
-    let detail = unsafe {
-        str::from_utf8(CStr::from_ptr(libc::gai_strerror(err)).to_bytes()).unwrap().to_owned()
-    };
-    Err(io::Error::new(
-        io::ErrorKind::Other,
-        &format!("failed to lookup address information: {}", detail)[..],
-    ))
+static DNS_LOCK: Mutex = Mutex::new();
+static mut DNS_CACHE: *mut Vec<DnsEntry> = ptr::null_mut();
+
+/// Resolves `host` to its IPv4 addresses, answering from the cache if it was
+/// looked up recently.
+pub fn lookup_host(host: &str) -> io::Result<Vec<Ipv4Addr>> {
+    let c_host = CString::new(host)?;
+    unsafe {
+        let _guard = DNS_LOCK.lock();
+        if DNS_CACHE.is_null() {
+            DNS_CACHE = Box::into_raw(box Vec::new());
+        }
+        let cache = &mut *DNS_CACHE;
+        let now = Instant::now();
+        cache.retain(|e| e.expires > now);
+        if let Some(entry) = cache.iter().find(|e| e.host.eq_ignore_ascii_case(host)) {
+            return Ok(entry.addrs.clone());
+        }
+
+        let addrs = gethostbyname(&c_host)?;
+        if cache.len() == DNS_CACHE_ENTRIES {
+            // Entries all live equally long, so the first one expires first.
+            cache.remove(0);
+        }
+        cache.push(DnsEntry {
+            host: host.to_owned(),
+            addrs: addrs.clone(),
+            expires: now + DNS_CACHE_TTL,
+        });
+        Ok(addrs)
+    }
+}
+
+// Must be called with `DNS_LOCK` held.
+unsafe fn gethostbyname(host: &CString) -> io::Result<Vec<Ipv4Addr>> {
+    let ent = ogc_sys::net_gethostbyname(host.as_ptr() as *const _);
+    if ent.is_null()
+        || (*ent).h_addrtype as c_int != libc::AF_INET
+        || (*ent).h_length != 4
+        || (*ent).h_addr_list.is_null()
+    {
+        return Err(io::Error::new(io::ErrorKind::Other,
+                                  "failed to lookup address information: host not found"));
+    }
+    let mut addrs = Vec::new();
+    let mut cur = (*ent).h_addr_list;
+    while !(*cur).is_null() {
+        let b = *(*cur as *const [u8; 4]);
+        let addr = Ipv4Addr::new(b[0], b[1], b[2], b[3]);
+        if !addrs.contains(&addr) {
+            addrs.push(addr);
+        }
+        cur = cur.add(1);
+    }
+    Ok(addrs)
 }
 
 impl Socket {
@@ -55,11 +187,10 @@ impl Socket {
 
     pub fn new_raw(fam: c_int, ty: c_int) -> io::Result<Socket> {
         unsafe {
-            let fd = cvt(ogc_sys::net_socket(fam as u32, ty as u32, 0))?;
-            let fd = FileDesc::new(fd);
-            fd.set_cloexec()?;
-            let socket = Socket(fd);
-            Ok(socket)
+            // There is no exec on this platform, so unlike other Unix-likes
+            // there is no close-on-exec flag to set here.
+            let fd = cvt_net(ogc_sys::net_socket(fam as u32, ty as u32, 0))?;
+            Ok(Socket(SocketDesc::new(fd)))
         }
     }
 
@@ -67,8 +198,7 @@ impl Socket {
         self.set_nonblocking(true)?;
         let r = unsafe {
             let (addrp, len) = addr.into_inner();
-            // ogc_sys::net_connect(self.0.raw(), addrp, len)
-            cvt(101)
+            cvt_net(ogc_sys::net_connect(self.0.raw(), addrp as *mut _, len))
         };
         self.set_nonblocking(false)?;
 
@@ -79,7 +209,11 @@ impl Socket {
             Err(e) => return Err(e),
         }
 
-        let mut pollfd = ogc_sys::pollsd { socket: self.0.raw(), events: ogc_sys::POLLOUT, revents: 0 };
+        let mut pollfd = ogc_sys::pollsd {
+            socket: self.0.raw(),
+            events: ogc_sys::POLLOUT as _,
+            revents: 0,
+        };
 
         if timeout.as_secs() == 0 && timeout.subsec_nanos() == 0 {
             return Err(io::Error::new(
@@ -107,18 +241,15 @@ impl Socket {
 
             let timeout = cmp::min(timeout, c_int::max_value() as u64) as c_int;
 
-            match unsafe { ogc_sys::net_poll(&mut pollfd, 1, timeout) } {
-                -1 => {
-                    let err = io::Error::last_os_error();
-                    if err.kind() != io::ErrorKind::Interrupted {
-                        return Err(err);
-                    }
-                }
-                0 => {}
-                _ => {
-                    // linux returns POLLOUT|POLLERR|POLLHUP for refused connections (!), so look
-                    // for POLLHUP rather than read readiness
-                    if pollfd.revents & ogc_sys::POLLHUP != 0 {
+            match cvt_net(unsafe { ogc_sys::net_poll(&mut pollfd, 1, timeout) }) {
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+                Ok(0) => {}
+                Ok(_) => {
+                    // Like linux, IOS flags refused connections as writable
+                    // too, so look for POLLERR/POLLHUP rather than write
+                    // readiness and ask the socket what went wrong.
+                    if pollfd.revents & (ogc_sys::POLLERR | ogc_sys::POLLHUP) as i32 != 0 {
                         let e = self.take_error()?.unwrap_or_else(|| {
                             io::Error::new(io::ErrorKind::Other, "no error set after POLLHUP")
                         });
@@ -132,18 +263,18 @@ impl Socket {
     }
 
     pub fn accept(&self, storage: *mut ogc_sys::sockaddr, len: *mut socklen_t) -> io::Result<Socket> {
-        let fd = cvt_r(|| unsafe { ogc_sys::net_accept(self.0.raw(), storage, len) })?;
-        let fd = FileDesc::new(fd);
-        fd.set_cloexec()?;
-        Ok(Socket(fd))
+        let fd = cvt_net_r(|| unsafe { ogc_sys::net_accept(self.0.raw(), storage, len) })?;
+        Ok(Socket(SocketDesc::new(fd)))
     }
 
     pub fn duplicate(&self) -> io::Result<Socket> {
-        self.0.duplicate().map(Socket)
+        // The IOS network stack has no equivalent of dup(2).
+        Err(io::Error::new(io::ErrorKind::Other,
+                           "duplicating sockets is not supported on this platform"))
     }
 
     fn recv_with_flags(&self, buf: &mut [u8], flags: c_int) -> io::Result<usize> {
-        let ret = cvt(unsafe {
+        let ret = cvt_net(unsafe {
             ogc_sys::net_recv(self.0.raw(), buf.as_mut_ptr() as *mut c_void, buf.len() as i32, flags as u32)
         })?;
         Ok(ret as usize)
@@ -157,9 +288,37 @@ impl Socket {
         self.recv_with_flags(buf, MSG_PEEK)
     }
 
+    // IOS has no readv/writev, so vectored I/O goes one slice at a time.
+    // Once some data has been read, the next slice is only filled if more is
+    // already waiting, so that the call doesn't block despite having data to
+    // return.
     pub fn read_vectored(&self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
-        //self.0.read_vectored(bufs)
-        unimplemented!()
+        let mut total = 0;
+        for buf in bufs.iter_mut().filter(|buf| !buf.is_empty()) {
+            if total > 0 && !self.readable()? {
+                break;
+            }
+            let n = match self.read(buf) {
+                Ok(n) => n,
+                Err(_) if total > 0 => break,
+                Err(e) => return Err(e),
+            };
+            total += n;
+            if n < buf.len() {
+                break;
+            }
+        }
+        Ok(total)
+    }
+
+    fn readable(&self) -> io::Result<bool> {
+        let mut pollfd = ogc_sys::pollsd {
+            socket: self.0.raw(),
+            events: ogc_sys::POLLIN as _,
+            revents: 0,
+        };
+        let n = cvt_net_r(|| unsafe { ogc_sys::net_poll(&mut pollfd, 1, 0) })?;
+        Ok(n > 0 && pollfd.revents & ogc_sys::POLLIN as i32 != 0)
     }
 
     fn recv_from_with_flags(
@@ -170,7 +329,7 @@ impl Socket {
         let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
         let mut addrlen = mem::size_of_val(&storage) as libc::socklen_t;
 
-        let n = cvt(unsafe {
+        let n = cvt_net(unsafe {
             ogc_sys::net_recvfrom(
                 self.0.raw(),
                 buf.as_mut_ptr() as *mut c_void,
@@ -196,8 +355,19 @@ impl Socket {
     }
 
     pub fn write_vectored(&self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
-        //self.0.write_vectored(bufs)
-        unimplemented!()
+        let mut total = 0;
+        for buf in bufs.iter().filter(|buf| !buf.is_empty()) {
+            let n = match self.write(buf) {
+                Ok(n) => n,
+                Err(_) if total > 0 => break,
+                Err(e) => return Err(e),
+            };
+            total += n;
+            if n < buf.len() {
+                break;
+            }
+        }
+        Ok(total)
     }
 
     pub fn set_timeout(&self, dur: Option<Duration>, kind: libc::c_int) -> io::Result<()> {
@@ -230,15 +400,14 @@ impl Socket {
     }
 
     pub fn timeout(&self, kind: libc::c_int) -> io::Result<Option<Duration>> {
-        // let raw: libc::timeval = getsockopt(self, libc::SOL_SOCKET, kind)?;
-        // if raw.tv_sec == 0 && raw.tv_usec == 0 {
-        //     Ok(None)
-        // } else {
-        //     let sec = raw.tv_sec as u64;
-        //     let nsec = (raw.tv_usec as u32) * 1000;
-        //     Ok(Some(Duration::new(sec, nsec)))
-        // }
-        unimplemented!()
+        let raw: libc::timeval = getsockopt(self, libc::SOL_SOCKET, kind)?;
+        if raw.tv_sec == 0 && raw.tv_usec == 0 {
+            Ok(None)
+        } else {
+            let sec = raw.tv_sec as u64;
+            let nsec = (raw.tv_usec as u32) * 1000;
+            Ok(Some(Duration::new(sec, nsec)))
+        }
     }
 
     pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
@@ -247,7 +416,7 @@ impl Socket {
             Shutdown::Read => libc::SHUT_RD,
             Shutdown::Both => libc::SHUT_RDWR,
         };
-        cvt(unsafe { ogc_sys::net_shutdown(self.0.raw(), how as u32) })?;
+        cvt_net(unsafe { ogc_sys::net_shutdown(self.0.raw(), how as u32) })?;
         Ok(())
     }
 
@@ -256,25 +425,26 @@ impl Socket {
     }
 
     pub fn nodelay(&self) -> io::Result<bool> {
-        // let raw: c_int = getsockopt(self, libc::IPPROTO_TCP, libc::TCP_NODELAY)?;
-        // Ok(raw != 0)
-        unimplemented!()
+        let raw: c_int = getsockopt(self, libc::IPPROTO_TCP, libc::TCP_NODELAY)?;
+        Ok(raw != 0)
     }
 
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
-        let mut nonblocking = nonblocking as i32 as *mut libc::c_void;
-        cvt(unsafe { ogc_sys::net_ioctl(*self.as_inner(), libc::FIONBIO, nonblocking) })
-            .map(|_| ())
+        // FIONBIO takes a pointer to the flag, not the flag itself.
+        let mut nonblocking = nonblocking as u32;
+        cvt_net(unsafe {
+            ogc_sys::net_ioctl(self.0.raw(), libc::FIONBIO as u32,
+                               &mut nonblocking as *mut u32 as *mut c_void)
+        }).map(|_| ())
     }
 
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
-        // let raw: c_int = getsockopt(self, libc::SOL_SOCKET, libc::SO_ERROR)?;
-        // if raw == 0 {
-        //     Ok(None)
-        // } else {
-        //     Ok(Some(io::Error::from_raw_os_error(raw as i32)))
-        // }
-        unimplemented!()
+        let raw: c_int = getsockopt(self, libc::SOL_SOCKET, libc::SO_ERROR)?;
+        if raw == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(io::Error::from_raw_os_error(raw as i32)))
+        }
     }
 }
 
@@ -286,7 +456,7 @@ impl AsInner<c_int> for Socket {
 
 impl FromInner<c_int> for Socket {
     fn from_inner(fd: c_int) -> Socket {
-        Socket(FileDesc::new(fd))
+        Socket(SocketDesc::new(fd))
     }
 }
 
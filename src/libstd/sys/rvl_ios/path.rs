@@ -1,5 +1,13 @@
 use crate::path::Prefix;
 use crate::ffi::OsStr;
+use crate::mem;
+
+fn os_str_as_u8_slice(s: &OsStr) -> &[u8] {
+    unsafe { mem::transmute(s) }
+}
+unsafe fn u8_slice_as_os_str(s: &[u8]) -> &OsStr {
+    mem::transmute(s)
+}
 
 #[inline]
 pub fn is_sep_byte(b: u8) -> bool {
@@ -11,8 +19,16 @@ pub fn is_verbatim_sep(b: u8) -> bool {
     b == b'/'
 }
 
-pub fn parse_prefix(_: &OsStr) -> Option<Prefix<'_>> {
-    None
+// devoptab looks up the device a path refers to by the name in front of the
+// first `:` (as in `sd:/apps`), provided no separator comes before it.
+// Everything else is resolved against the default device.
+pub fn parse_prefix(path: &OsStr) -> Option<Prefix<'_>> {
+    let path = os_str_as_u8_slice(path);
+    let end = path.iter().position(|&b| b == b':' || is_sep_byte(b))?;
+    if end == 0 || path[end] != b':' {
+        return None;
+    }
+    Some(Prefix::Device(unsafe { u8_slice_as_os_str(&path[..end]) }))
 }
 
 pub const MAIN_SEP_STR: &str = "/";
@@ -11,7 +11,10 @@ pub fn hashmap_random_keys() -> (u64, u64) {
     return v
 }
 
+pub use self::imp::fill_bytes;
+
 #[cfg(all(unix,
+          not(target_os = "rvl-ios"),
           not(target_os = "ios"),
           not(target_os = "openbsd"),
           not(target_os = "freebsd"),
@@ -87,6 +90,120 @@ mod imp {
     }
 }
 
+// IOS seeds a generator from the Starlet's hardware, but /dev/es has no
+// ioctl handing its output to the PPC side. It does use it for ES_Sign: the
+// signature is ECDSA with the console's per title key, which draws a fresh
+// nonce from that generator for every signature, so signing something yields
+// 60 bytes nobody can predict. Those go into a pool together with the
+// console specific device ID and the timing jitter of a batch of cheap ES
+// requests (the Starlet runs on its own clock and schedules its own
+// threads), which is all that's left when signing fails, e.g. when no title
+// context is set up. The pool is condensed into a key with the SHA-1 engine
+// (/dev/sha), and the output generated from that with the AES engine
+// (/dev/aes) in counter mode.
+#[cfg(target_os = "rvl-ios")]
+mod imp {
+    // Number of ES round trips timed per call. Each one takes a few
+    // microseconds, and only the low bits of every delta vary.
+    const SAMPLES: usize = 128;
+
+    const SIG_SIZE: usize = 0x3c;
+    const POOL_SIZE: usize = 8 + SAMPLES * 16 + SIG_SIZE;
+
+    // The IOS engines refuse buffers that aren't suitably aligned, SHA's
+    // input being the strictest at 64 bytes.
+    #[repr(align(64))]
+    struct Aligned<T>(T);
+
+    struct Pool {
+        buf: Aligned<[u8; POOL_SIZE]>,
+        len: usize,
+    }
+
+    impl Pool {
+        fn push(&mut self, bytes: &[u8]) {
+            self.buf.0[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+            self.len += bytes.len();
+        }
+
+        fn bytes(&self) -> &[u8] {
+            &self.buf.0[..self.len]
+        }
+    }
+
+    fn check(ret: i32, what: &str) {
+        if ret < 0 {
+            panic!("couldn't generate random bytes: {} failed with {}", what, ret);
+        }
+    }
+
+    pub fn fill_bytes(v: &mut [u8]) {
+        let mut pool = Pool { buf: Aligned([0; POOL_SIZE]), len: 0 };
+
+        let mut device_id = 0;
+        unsafe {
+            // A failing request leaves the ID at zero, which is fine: its
+            // only purpose is telling consoles apart.
+            ogc_sys::ES_GetDeviceID(&mut device_id);
+            pool.push(&device_id.to_be_bytes());
+            pool.push(&ogc_sys::gettime().to_be_bytes()[4..]);
+        }
+        for _ in 0..SAMPLES {
+            let (start, end) = unsafe {
+                let start = ogc_sys::gettime();
+                ogc_sys::ES_GetDeviceID(&mut device_id);
+                (start, ogc_sys::gettime())
+            };
+            pool.push(&end.wrapping_sub(start).to_be_bytes());
+            pool.push(&end.to_be_bytes());
+        }
+
+        let mut sig = Aligned([0u8; SIG_SIZE]);
+        let mut certs = Aligned([0u8; 0x180]);
+        let ret = unsafe {
+            ogc_sys::ES_Sign(pool.buf.0.as_mut_ptr(), pool.len as u32,
+                             sig.0.as_mut_ptr(), certs.0.as_mut_ptr())
+        };
+        // The certificate chain is the same for every signature.
+        if ret >= 0 {
+            pool.push(&sig.0);
+        }
+
+        // libogc keeps both devices open once initialized, and shares them
+        // with the rest of the program, so they're never closed here.
+        let mut digest = Aligned([0u8; 20]);
+        unsafe {
+            check(ogc_sys::SHA_Init(), "SHA_Init");
+            check(ogc_sys::SHA_Calculate(pool.bytes().as_ptr() as _, pool.len as u32,
+                                         digest.0.as_mut_ptr() as _),
+                  "SHA_Calculate");
+            check(ogc_sys::AES_Init(), "AES_Init");
+        }
+
+        // The first 128 bits of the digest are the key, the rest is the
+        // nonce in front of the 64-bit block counter.
+        let mut key = Aligned([0u8; 16]);
+        key.0.copy_from_slice(&digest.0[..16]);
+        let mut counter = Aligned([0u8; 16]);
+        counter.0[..4].copy_from_slice(&digest.0[16..]);
+        let mut block = Aligned([0u8; 16]);
+        for (i, chunk) in v.chunks_mut(16).enumerate() {
+            counter.0[8..].copy_from_slice(&(i as u64).to_be_bytes());
+            // The engine only does CBC, which for a single block with a zero
+            // IV is the plain block cipher.
+            let mut iv = Aligned([0u8; 16]);
+            unsafe {
+                check(ogc_sys::AES_Encrypt(key.0.as_ptr() as _, 16,
+                                           iv.0.as_mut_ptr() as _, 16,
+                                           counter.0.as_ptr() as _,
+                                           block.0.as_mut_ptr() as _, 16),
+                      "AES_Encrypt");
+            }
+            chunk.copy_from_slice(&block.0[..chunk.len()]);
+        }
+    }
+}
+
 #[cfg(target_os = "openbsd")]
 mod imp {
     use crate::sys::os::errno;
@@ -1,131 +1,178 @@
 use crate::cell::UnsafeCell;
-use crate::sync::atomic::{AtomicUsize, Ordering};
+use crate::sys::mutex::{self, Mutex};
 
+// libogc has no reader-writer lock of its own, so this one is assembled from
+// an LWP mutex guarding the bookkeeping below and two condition variables to
+// park readers and writers on. Writers are preferred: once a writer is
+// waiting, new readers queue up behind it instead of starving it.
 pub struct RWLock {
-    inner: UnsafeCell<libc::pthread_rwlock_t>,
-    write_locked: UnsafeCell<bool>, // guarded by the `inner` RwLock
-    num_readers: AtomicUsize,
+    lock: Mutex,
+    // Everything below is guarded by `lock`.
+    readers: UnsafeCell<ogc_sys::cond_t>,
+    writers: UnsafeCell<ogc_sys::cond_t>,
+    conds_initialized: UnsafeCell<bool>,
+    num_readers: UnsafeCell<usize>,
+    waiting_writers: UnsafeCell<usize>,
+    writer: UnsafeCell<ogc_sys::lwp_t>,
 }
 
 unsafe impl Send for RWLock {}
 unsafe impl Sync for RWLock {}
 
+// Value of `writer` while the lock isn't write locked (`LWP_THREAD_NULL`).
+const NO_WRITER: ogc_sys::lwp_t = 0xffff_ffff;
+
 impl RWLock {
     pub const fn new() -> RWLock {
+        // `new` has to be a `const fn` so that statics can hold an RWLock,
+        // which means the LWP objects can only be created on first use. The
+        // mutex takes care of itself; the condition variables are created
+        // the first time it is held.
         RWLock {
-            inner: UnsafeCell::new(libc::PTHREAD_RWLOCK_INITIALIZER),
-            write_locked: UnsafeCell::new(false),
-            num_readers: AtomicUsize::new(0),
+            lock: Mutex::new(),
+            readers: UnsafeCell::new(0),
+            writers: UnsafeCell::new(0),
+            conds_initialized: UnsafeCell::new(false),
+            num_readers: UnsafeCell::new(0),
+            waiting_writers: UnsafeCell::new(0),
+            writer: UnsafeCell::new(NO_WRITER),
+        }
+    }
+
+    #[inline]
+    unsafe fn lock(&self) {
+        self.lock.lock();
+        if !*self.conds_initialized.get() {
+            let r = ogc_sys::LWP_CondInit(self.readers.get());
+            assert_eq!(r, 0);
+            let r = ogc_sys::LWP_CondInit(self.writers.get());
+            assert_eq!(r, 0);
+            *self.conds_initialized.get() = true;
         }
     }
+
+    #[inline]
+    unsafe fn unlock(&self) {
+        self.lock.unlock();
+    }
+
+    #[inline]
+    unsafe fn write_locked(&self) -> bool {
+        *self.writer.get() != NO_WRITER
+    }
+
     #[inline]
     pub unsafe fn read(&self) {
-        let r = libc::pthread_rwlock_rdlock(self.inner.get());
-
-        // According to the pthread_rwlock_rdlock spec, this function **may**
-        // fail with EDEADLK if a deadlock is detected. On the other hand
-        // pthread mutexes will *never* return EDEADLK if they are initialized
-        // as the "fast" kind (which ours always are). As a result, a deadlock
-        // situation may actually return from the call to pthread_rwlock_rdlock
-        // instead of blocking forever (as mutexes and Windows rwlocks do). Note
-        // that not all unix implementations, however, will return EDEADLK for
-        // their rwlocks.
-        //
-        // We roughly maintain the deadlocking behavior by panicking to ensure
-        // that this lock acquisition does not succeed.
-        //
-        // We also check whether this lock is already write locked. This
-        // is only possible if it was write locked by the current thread and
-        // the implementation allows recursive locking. The POSIX standard
-        // doesn't require recursively locking a rwlock to deadlock, but we can't
-        // allow that because it could lead to aliasing issues.
-        if r == libc::EAGAIN {
-            panic!("rwlock maximum reader count exceeded");
-        } else if r == libc::EDEADLK || (r == 0 && *self.write_locked.get()) {
-            if r == 0 {
-                self.raw_unlock();
-            }
+        self.lock();
+
+        // Taking a read lock while this thread holds the write lock would
+        // block forever, and letting it succeed could lead to aliasing
+        // issues. Mirror what the pthread based implementations do when
+        // pthread_rwlock_rdlock reports EDEADLK and panic instead.
+        if *self.writer.get() == ogc_sys::LWP_GetSelf() {
+            self.unlock();
             panic!("rwlock read lock would result in deadlock");
-        } else {
-            assert_eq!(r, 0);
-            self.num_readers.fetch_add(1, Ordering::Relaxed);
         }
+
+        while self.write_locked() || *self.waiting_writers.get() != 0 {
+            let r = ogc_sys::LWP_CondWait(*self.readers.get(), mutex::handle(&self.lock));
+            debug_assert_eq!(r, 0);
+        }
+
+        if *self.num_readers.get() == usize::max_value() {
+            self.unlock();
+            panic!("rwlock maximum reader count exceeded");
+        }
+        *self.num_readers.get() += 1;
+
+        self.unlock();
     }
+
     #[inline]
     pub unsafe fn try_read(&self) -> bool {
-        let r = libc::pthread_rwlock_tryrdlock(self.inner.get());
-        if r == 0 {
-            if *self.write_locked.get() {
-                self.raw_unlock();
-                false
-            } else {
-                self.num_readers.fetch_add(1, Ordering::Relaxed);
-                true
-            }
-        } else {
-            false
+        self.lock();
+        let acquired = !self.write_locked() &&
+                       *self.waiting_writers.get() == 0 &&
+                       *self.num_readers.get() != usize::max_value();
+        if acquired {
+            *self.num_readers.get() += 1;
         }
+        self.unlock();
+        acquired
     }
+
     #[inline]
     pub unsafe fn write(&self) {
-        let r = libc::pthread_rwlock_wrlock(self.inner.get());
-        // See comments above for why we check for EDEADLK and write_locked. We
-        // also need to check that num_readers is 0.
-        if r == libc::EDEADLK || *self.write_locked.get() ||
-           self.num_readers.load(Ordering::Relaxed) != 0 {
-            if r == 0 {
-                self.raw_unlock();
-            }
+        self.lock();
+
+        // See comments above for why this panics rather than deadlocking.
+        let me = ogc_sys::LWP_GetSelf();
+        if *self.writer.get() == me {
+            self.unlock();
             panic!("rwlock write lock would result in deadlock");
-        } else {
+        }
+
+        *self.waiting_writers.get() += 1;
+        while self.write_locked() || *self.num_readers.get() != 0 {
+            let r = ogc_sys::LWP_CondWait(*self.writers.get(), mutex::handle(&self.lock));
             debug_assert_eq!(r, 0);
         }
-        *self.write_locked.get() = true;
+        *self.waiting_writers.get() -= 1;
+        *self.writer.get() = me;
+
+        self.unlock();
     }
+
     #[inline]
     pub unsafe fn try_write(&self) -> bool {
-        let r = libc::pthread_rwlock_trywrlock(self.inner.get());
-        if r == 0 {
-            if *self.write_locked.get() || self.num_readers.load(Ordering::Relaxed) != 0 {
-                self.raw_unlock();
-                false
-            } else {
-                *self.write_locked.get() = true;
-                true
-            }
-        } else {
-            false
+        self.lock();
+        let acquired = !self.write_locked() && *self.num_readers.get() == 0;
+        if acquired {
+            *self.writer.get() = ogc_sys::LWP_GetSelf();
         }
+        self.unlock();
+        acquired
     }
-    #[inline]
-    unsafe fn raw_unlock(&self) {
-        let r = libc::pthread_rwlock_unlock(self.inner.get());
-        debug_assert_eq!(r, 0);
-    }
+
     #[inline]
     pub unsafe fn read_unlock(&self) {
-        debug_assert!(!*self.write_locked.get());
-        self.num_readers.fetch_sub(1, Ordering::Relaxed);
-        self.raw_unlock();
+        self.lock();
+        debug_assert!(!self.write_locked());
+        debug_assert!(*self.num_readers.get() > 0);
+        *self.num_readers.get() -= 1;
+        if *self.num_readers.get() == 0 && *self.waiting_writers.get() != 0 {
+            let r = ogc_sys::LWP_CondSignal(*self.writers.get());
+            debug_assert_eq!(r, 0);
+        }
+        self.unlock();
     }
+
     #[inline]
     pub unsafe fn write_unlock(&self) {
-        debug_assert_eq!(self.num_readers.load(Ordering::Relaxed), 0);
-        debug_assert!(*self.write_locked.get());
-        *self.write_locked.get() = false;
-        self.raw_unlock();
+        self.lock();
+        debug_assert_eq!(*self.num_readers.get(), 0);
+        debug_assert_eq!(*self.writer.get(), ogc_sys::LWP_GetSelf());
+        *self.writer.get() = NO_WRITER;
+        // Hand the lock to the next writer if there is one, otherwise let
+        // every reader that queued up in the meantime in.
+        let r = if *self.waiting_writers.get() != 0 {
+            ogc_sys::LWP_CondSignal(*self.writers.get())
+        } else {
+            ogc_sys::LWP_CondBroadcast(*self.readers.get())
+        };
+        debug_assert_eq!(r, 0);
+        self.unlock();
     }
+
     #[inline]
     pub unsafe fn destroy(&self) {
-        let r = libc::pthread_rwlock_destroy(self.inner.get());
-        // On DragonFly pthread_rwlock_destroy() returns EINVAL if called on a
-        // rwlock that was just initialized with
-        // libc::PTHREAD_RWLOCK_INITIALIZER. Once it is used (locked/unlocked)
-        // or pthread_rwlock_init() is called, this behaviour no longer occurs.
-        if cfg!(target_os = "dragonfly") {
-            debug_assert!(r == 0 || r == libc::EINVAL);
-        } else {
+        // A lock that was never used never created its condition variables.
+        if *self.conds_initialized.get() {
+            let r = ogc_sys::LWP_CondDestroy(*self.writers.get());
+            debug_assert_eq!(r, 0);
+            let r = ogc_sys::LWP_CondDestroy(*self.readers.get());
             debug_assert_eq!(r, 0);
         }
+        self.lock.destroy();
     }
 }
@@ -1,206 +1,143 @@
 #![cfg_attr(test, allow(dead_code))]
 
-use self::imp::{make_handler, drop_handler};
-
-pub use self::imp::cleanup;
-pub use self::imp::init;
-
-pub struct Handler {
-    _data: *mut libc::c_void
-}
+// libogc maps memory with BATs only, so there are no pages to put a guard
+// page below a stack with. Overflows are caught two ways instead:
+//
+// * The lowest `CANARY_SIZE` bytes of every stack we know the bounds of are
+//   filled with a pattern, which `check` verifies. libogc has no hook into
+//   its scheduler, so that happens when a thread yields, sleeps, joins,
+//   waits on a condition variable (and so parks, blocks on a channel or on a
+//   contended `RwLock`) or exits, and when it starts to panic. Threads that
+//   block in LWP calls directly, and plain mutex contention, aren't covered.
+// * The data address breakpoint (DABR) watches the top doubleword of the
+//   canary of the thread that last passed a check, and traps stores to it
+//   with a DSI that `exception` hands to `breakpoint`. A thread preempted by
+//   another leaves the breakpoint on its own stack; the other thread can't
+//   legitimately store there, but a stale breakpoint is simply cleared when
+//   it does trigger. The store that hits the breakpoint is made with the
+//   stack pointer at most a frame below the watched word, and libogc's
+//   exception entry pushes its `frame_context` and our handler's frames
+//   below that, so the canary leaves room for them under the watched word.
+
+use crate::slice;
+use crate::sync::atomic::{AtomicBool, Ordering};
+use crate::sys::thread::guard::{self, Guard};
+use crate::sys_common::util::report_overflow;
+
+pub const CANARY_SIZE: usize = 1024;
+const CANARY: u32 = 0x5354_4b21;
+
+// DABR[BT], DABR[DW]: match stores with data address translation enabled.
+const DABR_WRITE: u32 = 0x0000_0006;
+
+// The thread the breakpoint belongs to and the doubleword it watches.
+static mut WATCH: Option<(ogc_sys::lwp_t, usize)> = None;
+
+// What `overflowed` runs on, since the overflowed stack has no room left.
+// There is only one, so it's taken by the first overflow for good.
+static mut ALT_STACK: [u64; 1024] = [0; 1024];
+static ALT_STACK_TAKEN: AtomicBool = AtomicBool::new(false);
+
+pub struct Handler;
 
 impl Handler {
     pub unsafe fn new() -> Handler {
-        make_handler()
+        Handler
     }
 }
 
-impl Drop for Handler {
-    fn drop(&mut self) {
-        unsafe {
-            drop_handler(self);
-        }
-    }
+pub unsafe fn init() {
+    check();
 }
 
-#[cfg(any(target_os = "linux",
-          target_os = "macos",
-          target_os = "dragonfly",
-          target_os = "freebsd",
-          target_os = "solaris",
-          all(target_os = "netbsd", not(target_vendor = "rumprun")),
-          target_os = "openbsd"))]
-mod imp {
-    use super::Handler;
-    use crate::mem;
-    use crate::ptr;
-
-    use libc::{sigaltstack, SIGSTKSZ, SS_DISABLE};
-    use libc::{sigaction, SIGBUS, SIG_DFL,
-               SA_SIGINFO, SA_ONSTACK, sighandler_t};
-    use libc::{mmap, munmap};
-    use libc::{SIGSEGV, PROT_READ, PROT_WRITE, MAP_PRIVATE, MAP_ANON};
-    use libc::MAP_FAILED;
-
-    use crate::sys_common::thread_info;
-
-
-    #[cfg(any(target_os = "linux", target_os = "android"))]
-    unsafe fn siginfo_si_addr(info: *mut libc::siginfo_t) -> usize {
-        #[repr(C)]
-        struct siginfo_t {
-            a: [libc::c_int; 3], // si_signo, si_errno, si_code
-            si_addr: *mut libc::c_void,
-        }
+pub unsafe fn cleanup() {
+    disarm();
+}
 
-        (*(info as *const siginfo_t)).si_addr as usize
-    }
+unsafe fn canary(guard: &Guard) -> &mut [u32] {
+    slice::from_raw_parts_mut(guard.start as *mut u32, (guard.end - guard.start) / 4)
+}
 
-    #[cfg(not(any(target_os = "linux", target_os = "android")))]
-    unsafe fn siginfo_si_addr(info: *mut libc::siginfo_t) -> usize {
-        (*info).si_addr as usize
+/// Fills the canary region at the bottom of a new stack.
+pub unsafe fn protect(guard: &Guard) {
+    for word in canary(guard) {
+        *word = CANARY;
     }
+}
 
-    // Signal handler for the SIGSEGV and SIGBUS handlers. We've got guard pages
-    // (unmapped pages) at the end of every thread's stack, so if a thread ends
-    // up running into the guard page it'll trigger this handler. We want to
-    // detect these cases and print out a helpful error saying that the stack
-    // has overflowed. All other signals, however, should go back to what they
-    // were originally supposed to do.
-    //
-    // This handler currently exists purely to print an informative message
-    // whenever a thread overflows its stack. We then abort to exit and
-    // indicate a crash, but to avoid a misleading SIGSEGV that might lead
-    // users to believe that unsafe code has accessed an invalid pointer; the
-    // SIGSEGV encountered when overflowing the stack is expected and
-    // well-defined.
-    //
-    // If this is not a stack overflow, the handler un-registers itself and
-    // then returns (to allow the original signal to be delivered again).
-    // Returning from this kind of signal handler is technically not defined
-    // to work when reading the POSIX spec strictly, but in practice it turns
-    // out many large systems and all implementations allow returning from a
-    // signal handler to work. For a more detailed explanation see the
-    // comments on #26458.
-    unsafe extern fn signal_handler(signum: libc::c_int,
-                                    info: *mut libc::siginfo_t,
-                                    _data: *mut libc::c_void) {
-        use crate::sys_common::util::report_overflow;
-
-        let guard = thread_info::stack_guard().unwrap_or(0..0);
-        let addr = siginfo_si_addr(info);
-
-        // If the faulting address is within the guard page, then we print a
-        // message saying so and abort.
-        if guard.start <= addr && addr < guard.end {
-            report_overflow();
-            rtabort!("stack overflow");
-        } else {
-            // Unregister ourselves by reverting back to the default behavior.
-            let mut action: sigaction = mem::zeroed();
-            action.sa_sigaction = SIG_DFL;
-            sigaction(signum, &action, ptr::null_mut());
-
-            // See comment above for why this function returns.
+/// Verifies the canary of the current thread's stack, aborting if it has
+/// been overwritten, and points the breakpoint at it.
+pub fn check() {
+    unsafe {
+        if let Some(guard) = guard::current() {
+            check_guard(&guard);
         }
     }
+}
 
-    static mut MAIN_ALTSTACK: *mut libc::c_void = ptr::null_mut();
-
-    pub unsafe fn init() {
-        let mut action: sigaction = mem::zeroed();
-        action.sa_flags = SA_SIGINFO | SA_ONSTACK;
-        action.sa_sigaction = signal_handler as sighandler_t;
-        sigaction(SIGSEGV, &action, ptr::null_mut());
-        sigaction(SIGBUS, &action, ptr::null_mut());
-
-        let handler = make_handler();
-        MAIN_ALTSTACK = handler._data;
-        mem::forget(handler);
-    }
-
-    pub unsafe fn cleanup() {
-        Handler { _data: MAIN_ALTSTACK };
+pub unsafe fn check_guard(guard: &Guard) {
+    if canary(guard).iter().any(|&word| word != CANARY) {
+        disarm();
+        report_overflow();
+        rtabort!("stack overflow");
     }
+    arm(guard);
+}
 
-    unsafe fn get_stackp() -> *mut libc::c_void {
-        let stackp = mmap(ptr::null_mut(),
-                          SIGSTKSZ,
-                          PROT_READ | PROT_WRITE,
-                          MAP_PRIVATE | MAP_ANON,
-                          -1,
-                          0);
-        if stackp == MAP_FAILED {
-            panic!("failed to allocate an alternative stack");
-        }
-        stackp
-    }
+unsafe fn set_dabr(dabr: u32) {
+    asm!("mtspr 1013, $0
+          isync" :: "r"(dabr) :: "volatile");
+}
 
-    #[cfg(any(target_os = "linux",
-              target_os = "macos",
-              target_os = "freebsd",
-              target_os = "netbsd",
-              target_os = "openbsd",
-              target_os = "solaris"))]
-    unsafe fn get_stack() -> libc::stack_t {
-        libc::stack_t { ss_sp: get_stackp(), ss_flags: 0, ss_size: SIGSTKSZ }
-    }
+unsafe fn arm(guard: &Guard) {
+    let addr = guard.end - 8;
+    let level = ogc_sys::IRQ_Disable();
+    WATCH = Some((ogc_sys::LWP_GetSelf(), addr));
+    set_dabr(addr as u32 | DABR_WRITE);
+    ogc_sys::IRQ_Restore(level);
+}
 
-    #[cfg(target_os = "dragonfly")]
-    unsafe fn get_stack() -> libc::stack_t {
-        libc::stack_t { ss_sp: get_stackp() as *mut i8, ss_flags: 0, ss_size: SIGSTKSZ }
-    }
+/// Clears the breakpoint, which must not outlive the stack it watches.
+pub unsafe fn disarm() {
+    let level = ogc_sys::IRQ_Disable();
+    WATCH = None;
+    set_dabr(0);
+    ogc_sys::IRQ_Restore(level);
+}
 
-    pub unsafe fn make_handler() -> Handler {
-        let mut stack = mem::zeroed();
-        sigaltstack(ptr::null(), &mut stack);
-        // Configure alternate signal stack, if one is not already set.
-        if stack.ss_flags & SS_DISABLE != 0 {
-            stack = get_stack();
-            sigaltstack(&stack, ptr::null_mut());
-            Handler { _data: stack.ss_sp as *mut libc::c_void }
-        } else {
-            Handler { _data: ptr::null_mut() }
+/// Handles a DSI caused by the breakpoint, in exception context. Returns
+/// `false` if the exception is to be left to libogc's handler instead, which
+/// halts the console.
+pub unsafe fn breakpoint(frame: &mut ogc_sys::frame_context, dar: u32) -> bool {
+    match WATCH {
+        Some((thread, addr)) if thread == ogc_sys::LWP_GetSelf()
+                                && dar as usize & !7 == addr => {
+            // Another thread overflowed and is still reporting it, so there
+            // is no stack left to report this one on.
+            if ALT_STACK_TAKEN.swap(true, Ordering::SeqCst) {
+                return false;
+            }
+            // Continue in `overflowed` on the spare stack, with a null back
+            // chain and LR save word on top.
+            let top = &mut ALT_STACK[ALT_STACK.len() - 1];
+            *top = 0;
+            frame.GPR[1] = top as *mut u64 as u32;
+            frame.SRR0 = overflowed as usize as u32;
         }
-    }
-
-    pub unsafe fn drop_handler(handler: &mut Handler) {
-        if !handler._data.is_null() {
-            let stack =  libc::stack_t {
-                ss_sp: ptr::null_mut(),
-                ss_flags: SS_DISABLE,
-                // Workaround for bug in macOS implementation of sigaltstack
-                // UNIX2003 which returns ENOMEM when disabling a stack while
-                // passing ss_size smaller than MINSIGSTKSZ. According to POSIX
-                // both ss_sp and ss_size should be ignored in this case.
-                ss_size: SIGSTKSZ,
-            };
-            sigaltstack(&stack, ptr::null_mut());
-            munmap(handler._data, SIGSTKSZ);
+        // Another thread's stack; retrying the store without a breakpoint
+        // lets it through.
+        _ => {
+            WATCH = None;
+            set_dabr(0);
         }
     }
+    true
 }
 
-#[cfg(not(any(target_os = "linux",
-              target_os = "macos",
-              target_os = "dragonfly",
-              target_os = "freebsd",
-              target_os = "solaris",
-              all(target_os = "netbsd", not(target_vendor = "rumprun")),
-              target_os = "openbsd")))]
-mod imp {
-    use crate::ptr;
-
-    pub unsafe fn init() {
-    }
-
-    pub unsafe fn cleanup() {
-    }
-
-    pub unsafe fn make_handler() -> super::Handler {
-        super::Handler { _data: ptr::null_mut() }
-    }
-
-    pub unsafe fn drop_handler(_handler: &mut super::Handler) {
+extern "C" fn overflowed() -> ! {
+    unsafe {
+        disarm();
+        report_overflow();
     }
+    rtabort!("stack overflow")
 }
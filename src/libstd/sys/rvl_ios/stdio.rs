@@ -1,11 +1,81 @@
 use crate::io;
+use crate::net::{SocketAddr, UdpSocket};
+use crate::ptr;
 use crate::sys::fd::FileDesc;
+use crate::sys_common::mutex::Mutex;
 use crate::mem::ManuallyDrop;
 
 pub struct Stdin(());
 pub struct Stdout(());
 pub struct Stderr(());
 
+/// Where the output of `Stdout`, `Stderr` and `panic_output` ends up.
+///
+/// Nothing reads fds 1 and 2 unless libogc's console has been set up, so
+/// programs pick one of these at startup through `os::rvl_ios::stdio`.
+pub enum Sink {
+    /// newlib's stdout/stderr devoptab, which libogc's framebuffer console
+    /// takes over once initialized.
+    Console,
+    /// The USB Gecko in the given EXI memory card slot.
+    UsbGecko(i32),
+    /// The EXI UART Dolphin logs as OSReport output.
+    Dolphin,
+    /// UDP datagrams sent from `socket` to `addr`.
+    Udp { socket: UdpSocket, addr: SocketAddr },
+}
+
+// We never call `LOCK.init()`, so it is UB to attempt to
+// acquire this mutex reentrantly! It only guards `SINK` itself: writing to a
+// sink can fault or panic, and the resulting panic message is written out
+// through here again.
+static LOCK: Mutex = Mutex::new();
+// Null means `Sink::Console`.
+static mut SINK: *mut Sink = ptr::null_mut();
+
+pub fn set_sink(sink: Sink) {
+    // The previous sink may still be written to by other threads, which
+    // don't hold `LOCK` while they do, so it is leaked rather than dropped.
+    // Programs pick a sink once at startup anyway.
+    unsafe {
+        let _guard = LOCK.lock();
+        SINK = Box::into_raw(box sink);
+    }
+}
+
+fn write(fd: libc::c_int, buf: &[u8]) -> io::Result<usize> {
+    unsafe {
+        let sink = {
+            let _guard = LOCK.lock();
+            SINK
+        };
+        if sink.is_null() {
+            return ManuallyDrop::new(FileDesc::new(fd)).write(buf);
+        }
+        match *sink {
+            Sink::Console => ManuallyDrop::new(FileDesc::new(fd)).write(buf),
+            Sink::UsbGecko(channel) => {
+                let len = buf.len() as i32;
+                let r = ogc_sys::usb_sendbuffer_safe(channel, buf.as_ptr() as *const _, len);
+                if r < 0 {
+                    Err(io::Error::new(io::ErrorKind::Other, "failed to write to the USB Gecko"))
+                } else {
+                    Ok(r as usize)
+                }
+            }
+            Sink::Dolphin => {
+                // SYS_Report is printf-like, so never hand it the text itself
+                // as the format string.
+                ogc_sys::SYS_Report(b"%.*s\0".as_ptr() as *const _,
+                                    buf.len() as libc::c_int,
+                                    buf.as_ptr());
+                Ok(buf.len())
+            }
+            Sink::Udp { ref socket, addr } => socket.send_to(buf, addr),
+        }
+    }
+}
+
 impl Stdin {
     pub fn new() -> io::Result<Stdin> { Ok(Stdin(())) }
 }
@@ -26,13 +96,9 @@ impl Stdout {
 
 impl io::Write for Stdout {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        ManuallyDrop::new(FileDesc::new(libc::STDOUT_FILENO)).write(buf)
+        write(libc::STDOUT_FILENO, buf)
     }
 
-    // fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
-    //     ManuallyDrop::new(FileDesc::new(libc::STDOUT_FILENO)).write_vectored(bufs)
-    // }
-
     fn flush(&mut self) -> io::Result<()> {
         Ok(())
     }
@@ -44,13 +110,9 @@ impl Stderr {
 
 impl io::Write for Stderr {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        ManuallyDrop::new(FileDesc::new(libc::STDERR_FILENO)).write(buf)
+        write(libc::STDERR_FILENO, buf)
     }
 
-    // fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
-    //     ManuallyDrop::new(FileDesc::new(libc::STDERR_FILENO)).write_vectored(bufs)
-    // }
-
     fn flush(&mut self) -> io::Result<()> {
         Ok(())
     }
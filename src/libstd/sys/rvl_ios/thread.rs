@@ -1,9 +1,15 @@
+use crate::alloc::{GlobalAlloc, Layout, System};
 use crate::cmp;
-use crate::ffi::CStr;
+use crate::ffi::{CStr, CString};
 use crate::io;
 use crate::mem;
+use crate::panic;
 use crate::ptr;
+use crate::sync::Arc;
+use crate::sync::atomic::{AtomicBool, Ordering};
 use crate::sys::os;
+use crate::sys::stack_overflow;
+use crate::sys_common::mutex::Mutex;
 use crate::time::Duration;
 
 use crate::sys_common::thread::*;
@@ -14,14 +20,149 @@ pub const DEFAULT_MIN_STACK_SIZE: usize = 2 * 1024 * 1024;
 pub const DEFAULT_MIN_STACK_SIZE: usize = 1024 * 1024;
 
 pub struct Thread {
-    id: libc::pthread_t,
+    id: ogc_sys::lwp_t,
+    exit: Arc<Exit>,
 }
 
-// Some platforms may have pthread_t as a pointer in which case we still want
-// a thread to be Send/Sync
+// Posted by a thread once it has finished running Rust code. libogc reuses
+// the handle of a finished thread, so `join` can't wait on the handle
+// itself: by then it may well belong to an unrelated thread. For the same
+// reason nothing may be done with the handle once `finished` is set.
+struct Exit {
+    sem: ogc_sys::sem_t,
+    finished: AtomicBool,
+}
+
+impl Exit {
+    fn new() -> io::Result<Exit> {
+        let mut sem: ogc_sys::sem_t = unsafe { mem::zeroed() };
+        match unsafe { ogc_sys::LWP_SemInit(&mut sem, 0, 1) } {
+            0 => Ok(Exit { sem, finished: AtomicBool::new(false) }),
+            n => Err(io::Error::from_raw_os_error(n)),
+        }
+    }
+}
+
+impl Drop for Exit {
+    fn drop(&mut self) {
+        let r = unsafe { ogc_sys::LWP_SemDestroy(self.sem) };
+        debug_assert_eq!(r, 0);
+    }
+}
+
+// `lwp_t` is a plain handle, but keep the thread Send/Sync regardless of how
+// libogc chooses to represent it.
 unsafe impl Send for Thread {}
 unsafe impl Sync for Thread {}
 
+// LWP priorities run from LWP_PRIO_IDLE (0) to LWP_PRIO_HIGHEST (127), and a
+// thread with a higher priority always preempts one with a lower priority.
+pub const MAX_PRIORITY: u8 = 127;
+
+// Priority of threads spawned without an explicit one: the one libogc starts
+// the main thread at, so that spawned threads share the CPU with it.
+pub const DEFAULT_PRIORITY: u8 = 64;
+
+// libogc has nowhere to attach a name to an LWP thread, so the names given to
+// `set_name` are kept here for as long as the thread runs.
+static NAMES_LOCK: Mutex = Mutex::new();
+static mut NAMES: *mut Vec<(ogc_sys::lwp_t, CString)> = ptr::null_mut();
+
+unsafe fn with_names<R>(f: impl FnOnce(&mut Vec<(ogc_sys::lwp_t, CString)>) -> R) -> R {
+    let _guard = NAMES_LOCK.lock();
+    if NAMES.is_null() {
+        NAMES = Box::into_raw(box Vec::new());
+    }
+    f(&mut *NAMES)
+}
+
+/// Returns the name set by `Thread::set_name` on the given LWP thread, if it
+/// is still running.
+pub fn name_of(thread: ogc_sys::lwp_t) -> Option<CString> {
+    unsafe {
+        with_names(|names| names.iter().find(|e| e.0 == thread).map(|e| e.1.clone()))
+    }
+}
+
+// Stacks are allocated here rather than by LWP_CreateThread so that their
+// bounds are known, for the canary at their bottom. That makes freeing them
+// our job, which can't happen until the thread has switched away for the
+// last time. Exiting threads push their stack onto this list with
+// interrupts disabled and leave them disabled until libogc switches away
+// from the thread for good. Every other thread frees the list whenever it
+// passes through here: spawning, joining or dropping a thread, sleeping,
+// yielding and naming itself. The link lives in the dead stack itself, as
+// the list is pushed to without being able to allocate.
+struct DeadStack {
+    next: *mut DeadStack,
+    size: usize,
+}
+
+static mut DEAD_STACKS: *mut DeadStack = ptr::null_mut();
+
+const STACK_ALIGN: usize = 32;
+
+#[derive(Copy, Clone)]
+struct Stack {
+    base: *mut u8,
+    size: usize,
+}
+
+impl Stack {
+    fn new(size: usize) -> io::Result<Stack> {
+        let size = (size + stack_overflow::CANARY_SIZE + STACK_ALIGN - 1) & !(STACK_ALIGN - 1);
+        let base = unsafe { System.alloc(Stack::layout(size)) };
+        if base.is_null() {
+            return Err(io::Error::from_raw_os_error(libc::ENOMEM));
+        }
+        let stack = Stack { base, size };
+        unsafe {
+            stack_overflow::protect(&stack.guard());
+        }
+        Ok(stack)
+    }
+
+    fn layout(size: usize) -> Layout {
+        Layout::from_size_align(size, STACK_ALIGN).unwrap()
+    }
+
+    fn guard(&self) -> guard::Guard {
+        let base = self.base as usize;
+        base..base + stack_overflow::CANARY_SIZE
+    }
+
+    unsafe fn free(self) {
+        System.dealloc(self.base, Stack::layout(self.size));
+    }
+
+    // Must be called with interrupts disabled, which they have to stay
+    // until the thread is gone.
+    unsafe fn bury(self) {
+        let dead = self.base as *mut DeadStack;
+        ptr::write(dead, DeadStack { next: DEAD_STACKS, size: self.size });
+        DEAD_STACKS = dead;
+    }
+
+    unsafe fn free_dead() {
+        let level = ogc_sys::IRQ_Disable();
+        let mut dead = DEAD_STACKS;
+        DEAD_STACKS = ptr::null_mut();
+        ogc_sys::IRQ_Restore(level);
+
+        while !dead.is_null() {
+            let DeadStack { next, size } = ptr::read(dead);
+            Stack { base: dead as *mut u8, size }.free();
+            dead = next;
+        }
+    }
+}
+
+struct Start {
+    main: Box<dyn FnOnce()>,
+    stack: Stack,
+    exit: Arc<Exit>,
+}
+
 // The pthread_attr_setstacksize symbol doesn't exist in the libogc libc,
 // so we have to not link to it to satisfy the linker.
 #[cfg(not(target_os = "rvl-ios"))]
@@ -34,13 +175,16 @@ unsafe fn pthread_attr_setstacksize(
 
 impl Thread {
     // unsafe: see thread::Builder::spawn_unchecked for safety requirements
-    pub unsafe fn new(stack: usize, p: Box<dyn FnOnce()>) -> io::Result<Thread> {
-        let p = box p;
-        let mut native: libc::pthread_t = mem::zeroed();
+    pub unsafe fn new(stack: usize, priority: u8, p: Box<dyn FnOnce()>) -> io::Result<Thread> {
+        assert!(priority <= MAX_PRIORITY, "LWP priorities range from 0 to {}", MAX_PRIORITY);
+        let mut native: ogc_sys::lwp_t = mem::zeroed();
         // let mut attr: libc::pthread_attr_t = mem::zeroed();
         // assert_eq!(libc::pthread_attr_init(&mut attr), 0);
 
-        let stack_size = cmp::max(stack, min_stack_size());
+        Stack::free_dead();
+        let exit = Arc::new(Exit::new()?);
+        let stack = Stack::new(cmp::max(stack, min_stack_size()))?;
+        let start = Box::into_raw(box Start { main: p, stack, exit: exit.clone() });
 
         // match pthread_attr_setstacksize(&mut attr,
         //                                 stack_size) {
@@ -62,48 +206,87 @@ impl Thread {
         let ret = ogc_sys::LWP_CreateThread(
             &mut native,
             Some(thread_start),
-            &*p as *const _ as *mut _,
-            ptr::null_mut(),
-            stack_size as u32,
-            0,
+            start as *mut _,
+            stack.base as *mut _,
+            stack.size as u32,
+            priority,
         );
         // assert_eq!(libc::pthread_attr_destroy(&mut attr), 0);
 
         return if ret != 0 {
+            Box::from_raw(start);
+            stack.free();
             Err(io::Error::from_raw_os_error(ret))
         } else {
-            mem::forget(p); // ownership passed to pthread_create
-            Ok(Thread { id: native })
+            // ownership of `start` passed to LWP_CreateThread
+            Ok(Thread { id: native, exit })
         };
 
-        extern "C" fn thread_start(main: *mut libc::c_void) -> *mut libc::c_void {
+        extern "C" fn thread_start(start: *mut libc::c_void) -> *mut libc::c_void {
+            let Start { main, stack, exit } = unsafe { *Box::from_raw(start as *mut Start) };
             unsafe {
-                start_thread(main as *mut u8);
+                crate::sys::thread_local::forget_stale();
+                guard::set(stack.guard());
+                stack_overflow::check_guard(&stack.guard());
+            }
+
+            // The thread's closure catches its own panics and hands them to
+            // the `JoinHandle`, but a TLS destructor may still panic below.
+            // libogc's thread trampoline has no unwind info, so a panic
+            // must never get past this frame.
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| unsafe {
+                start_thread(Box::into_raw(box main) as *mut u8);
+                // Check the canary while the thread's info, and with it its
+                // name for the report, is still in TLS. The breakpoint stays
+                // armed over the destructors.
+                stack_overflow::check_guard(&stack.guard());
+                // There is no pthread_exit hook to run TLS destructors for
+                // us, so do it before the LWP thread returns.
+                crate::sys::thread_local::run_dtors();
+            }));
+            if result.is_err() {
+                rtabort!("thread local panicked on drop");
+            }
+            unsafe {
+                let me = ogc_sys::LWP_GetSelf();
+                with_names(|names| names.retain(|e| e.0 != me));
+
+                exit.finished.store(true, Ordering::SeqCst);
+                let r = ogc_sys::LWP_SemPost(exit.sem);
+                debug_assert_eq!(r, 0);
+                drop(exit);
+
+                stack_overflow::disarm();
+                ogc_sys::IRQ_Disable();
+                stack.bury();
             }
             ptr::null_mut()
         }
     }
 
     pub fn yield_now() {
+        stack_overflow::check();
+        unsafe { Stack::free_dead() }
         unsafe {
             ogc_sys::LWP_YieldThread();
         }
         // debug_assert_eq!(ret, 0);
     }
 
-    #[cfg(any(
-        target_env = "newlib",
-        target_os = "solaris",
-        target_os = "haiku",
-        target_os = "l4re",
-        target_os = "emscripten",
-        target_os = "hermit"
-    ))]
-    pub fn set_name(_name: &CStr) {
-        // Newlib, Illumos, Haiku, and Emscripten have no way to set a thread name.
+    pub fn set_name(name: &CStr) {
+        unsafe {
+            Stack::free_dead();
+            let me = ogc_sys::LWP_GetSelf();
+            with_names(|names| {
+                names.retain(|e| e.0 != me);
+                names.push((me, name.to_owned()));
+            })
+        }
     }
 
     pub fn sleep(dur: Duration) {
+        stack_overflow::check();
+        unsafe { Stack::free_dead() }
         let mut secs = dur.as_secs();
         let mut nsecs = dur.subsec_nanos() as _;
 
@@ -128,42 +311,96 @@ impl Thread {
     }
 
     pub fn join(self) {
+        stack_overflow::check();
         unsafe {
-            let ret = ogc_sys::LWP_JoinThread(self.id, ptr::null_mut());
-            mem::forget(self);
+            let ret = ogc_sys::LWP_SemWait(self.exit.sem);
             assert!(ret == 0, "failed to join thread: {}", io::Error::from_raw_os_error(ret));
+            Stack::free_dead();
         }
     }
 
-    pub fn id(&self) -> libc::pthread_t {
+    // Does nothing once the thread has finished, as its handle may belong to
+    // another thread by then.
+    pub fn set_priority(&self, priority: u8) {
+        assert!(priority <= MAX_PRIORITY, "LWP priorities range from 0 to {}", MAX_PRIORITY);
+        unsafe {
+            // The thread can't finish between the check and the call with
+            // interrupts disabled.
+            let level = ogc_sys::IRQ_Disable();
+            if !self.exit.finished.load(Ordering::SeqCst) {
+                ogc_sys::LWP_SetThreadPriority(self.id, priority as u32);
+            }
+            ogc_sys::IRQ_Restore(level);
+        }
+    }
+
+    pub fn id(&self) -> ogc_sys::lwp_t {
         self.id
     }
 
-    pub fn into_id(self) -> libc::pthread_t {
-        let id = self.id;
-        mem::forget(self);
-        id
+    pub fn into_id(self) -> ogc_sys::lwp_t {
+        self.id
     }
 }
 
 impl Drop for Thread {
     fn drop(&mut self) {
-        // let ret = unsafe { libc::pthread_detach(self.id) };
-        // debug_assert_eq!(ret, 0);
-        unimplemented!()
+        // libogc has no LWP_DetachThread because every LWP thread already
+        // behaves like a detached one: once its entry point returns the
+        // thread closes itself and frees its control block. Detaching is
+        // thus simply not joining, and the handle must not be used again
+        // since libogc reuses them. The exit semaphore goes away with
+        // whichever of the thread and this handle lets go of it last.
+        //
+        // The stack can't be freed by the thread itself, so it is only
+        // reclaimed the next time another thread frees the dead stacks (see
+        // `DeadStack`), which may well be a while for a program that is done
+        // spawning threads.
+        unsafe { Stack::free_dead() }
     }
 }
 
 #[cfg(target_os = "rvl-ios")]
 #[cfg_attr(test, allow(dead_code))]
 pub mod guard {
+    use crate::cell::Cell;
     use crate::ops::Range;
+    use crate::sys::stack_overflow;
+
+    // The canary region of the current thread's stack, as a `Guard` isn't
+    // `Copy`. Empty for threads not started by `Thread::new`.
+    thread_local!(static GUARD: Cell<(usize, usize)> = Cell::new((0, 0)));
+
+    extern "C" {
+        // The bottom and top of the main thread's stack, from libogc's
+        // linker script.
+        static __stack_end: u8;
+        static __stack_addr: u8;
+    }
+
     pub type Guard = Range<usize>;
+
+    pub(super) fn set(guard: Guard) {
+        GUARD.with(|g| g.set((guard.start, guard.end)));
+    }
+
     pub unsafe fn current() -> Option<Guard> {
-        None
+        match GUARD.try_with(|g| g.get()) {
+            Ok((start, end)) if start != end => Some(start..end),
+            _ => None,
+        }
     }
+
     pub unsafe fn init() -> Option<Guard> {
-        None
+        let base = &__stack_end as *const u8 as usize;
+        let top = &__stack_addr as *const u8 as usize;
+        if top - base <= 2 * stack_overflow::CANARY_SIZE {
+            return None;
+        }
+        let guard = base..base + stack_overflow::CANARY_SIZE;
+        stack_overflow::protect(&guard);
+        set(guard.clone());
+        Some(guard)
     }
 }
 
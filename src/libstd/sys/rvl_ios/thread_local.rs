@@ -1,37 +1,160 @@
 #![allow(dead_code)] // not used on all platforms
 
-//use crate::mem;
+// libogc has no pthread_key_* equivalent, so keys are handed out from a
+// process wide table and every LWP thread gets its own list of values, looked
+// up by `LWP_GetSelf()`. Destructors are run by `run_dtors`, which the thread
+// start routine in `sys::thread` calls once the thread's closure returns.
 
-pub type Key = libc::pthread_key_t;
+use crate::mem;
+use crate::ptr;
+use crate::sys_common::mutex::Mutex;
+
+pub type Key = usize;
+
+type Dtor = unsafe extern fn(*mut u8);
+
+struct Registry {
+    // Indexed by key. Key 0 is never handed out since `StaticKey` uses it as
+    // its "not yet created" sentinel.
+    keys: Vec<Option<Option<Dtor>>>,
+    // The values of every thread that has set at least one key.
+    threads: Vec<(ogc_sys::lwp_t, Vec<*mut u8>)>,
+}
+
+static LOCK: Mutex = Mutex::new();
+static mut REGISTRY: *mut Registry = ptr::null_mut();
+
+// Number of times destructors are re-run for keys that were set again by
+// another destructor, matching PTHREAD_DESTRUCTOR_ITERATIONS.
+const DESTRUCTOR_ITERATIONS: usize = 4;
+
+// We never call `LOCK.init()`, so the closure must not call back into this
+// module while the registry is borrowed.
+unsafe fn with_registry<R>(f: impl FnOnce(&mut Registry) -> R) -> R {
+    let _guard = LOCK.lock();
+    if REGISTRY.is_null() {
+        REGISTRY = Box::into_raw(box Registry {
+            keys: vec![None],
+            threads: Vec::new(),
+        });
+    }
+    f(&mut *REGISTRY)
+}
+
+impl Registry {
+    fn values(&mut self, thread: ogc_sys::lwp_t) -> Option<&mut Vec<*mut u8>> {
+        self.threads.iter_mut().find(|e| e.0 == thread).map(|e| &mut e.1)
+    }
+}
 
 #[inline]
 pub unsafe fn create(dtor: Option<unsafe extern fn(*mut u8)>) -> Key {
-    let mut key = 0;
-    //assert_eq!(libc::pthread_key_create(&mut key, mem::transmute(dtor)), 0);
-    key
+    with_registry(|reg| {
+        match reg.keys.iter().skip(1).position(|k| k.is_none()) {
+            Some(i) => {
+                reg.keys[i + 1] = Some(dtor);
+                i + 1
+            }
+            None => {
+                reg.keys.push(Some(dtor));
+                reg.keys.len() - 1
+            }
+        }
+    })
 }
 
 #[inline]
 pub unsafe fn set(key: Key, value: *mut u8) {
-    //let r = libc::pthread_setspecific(key, value as *mut _);
-    //debug_assert_eq!(r, 0);
-    unimplemented!()
+    let me = ogc_sys::LWP_GetSelf();
+    with_registry(|reg| {
+        debug_assert!(reg.keys.get(key).map_or(false, |k| k.is_some()));
+        if reg.values(me).is_none() {
+            reg.threads.push((me, Vec::new()));
+        }
+        let values = reg.values(me).unwrap();
+        if values.len() <= key {
+            values.resize(key + 1, ptr::null_mut());
+        }
+        values[key] = value;
+    })
 }
 
 #[inline]
 pub unsafe fn get(key: Key) -> *mut u8 {
-    //libc::pthread_getspecific(key) as *mut u8
-    unimplemented!()
+    let me = ogc_sys::LWP_GetSelf();
+    with_registry(|reg| {
+        reg.values(me)
+            .and_then(|values| values.get(key).cloned())
+            .unwrap_or(ptr::null_mut())
+    })
 }
 
 #[inline]
 pub unsafe fn destroy(key: Key) {
-    //let r = libc::pthread_key_delete(key);
-    //debug_assert_eq!(r, 0);
-    unimplemented!()
+    with_registry(|reg| {
+        debug_assert!(reg.keys.get(key).map_or(false, |k| k.is_some()));
+        reg.keys[key] = None;
+        // Like pthread_key_delete, this doesn't run any destructors, it only
+        // forgets the values so a later key with the same index starts empty.
+        for &mut (_, ref mut values) in reg.threads.iter_mut() {
+            if let Some(value) = values.get_mut(key) {
+                *value = ptr::null_mut();
+            }
+        }
+    })
 }
 
 #[inline]
 pub fn requires_synchronized_create() -> bool {
     false
 }
+
+/// Forgets any values left under the calling thread's LWP handle.
+///
+/// libogc reuses the handles of finished threads, and only threads started
+/// by `Thread::new` clean up after themselves in `run_dtors`. A thread
+/// created directly through `LWP_CreateThread` that used thread locals
+/// leaves its values behind, which must not show up in the next thread to
+/// get its handle. Their destructors can't run on another thread, so they
+/// are leaked.
+///
+/// Must be called on a new thread before it uses any thread local.
+pub unsafe fn forget_stale() {
+    let me = ogc_sys::LWP_GetSelf();
+    with_registry(|reg| reg.threads.retain(|e| e.0 != me));
+}
+
+/// Runs the destructors of every key the calling thread holds a non-null
+/// value for and drops the thread's value table afterwards.
+///
+/// Must be called on the exiting thread itself, after the last use of any
+/// thread local.
+pub unsafe fn run_dtors() {
+    let me = ogc_sys::LWP_GetSelf();
+    for _ in 0..DESTRUCTOR_ITERATIONS {
+        // Collect the pending destructors first so they run without the
+        // registry lock held; they are free to touch other thread locals.
+        let pending = with_registry(|reg| {
+            let mut pending = Vec::new();
+            let Registry { ref keys, ref mut threads } = *reg;
+            if let Some(&mut (_, ref mut values)) = threads.iter_mut().find(|e| e.0 == me) {
+                for (key, value) in values.iter_mut().enumerate() {
+                    if value.is_null() {
+                        continue;
+                    }
+                    if let Some(&Some(Some(dtor))) = keys.get(key) {
+                        pending.push((dtor, mem::replace(value, ptr::null_mut())));
+                    }
+                }
+            }
+            pending
+        });
+        if pending.is_empty() {
+            break;
+        }
+        for (dtor, value) in pending {
+            dtor(value);
+        }
+    }
+    with_registry(|reg| reg.threads.retain(|e| e.0 != me));
+}
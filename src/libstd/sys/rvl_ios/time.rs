@@ -240,14 +240,20 @@ mod inner {
 #[cfg(target_os = "rvl-ios")]
 mod inner {
     use crate::fmt;
-    use crate::sys::cvt;
     use crate::time::Duration;
 
     use super::Timespec;
+    use super::NSEC_PER_SEC;
 
-    #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    // The timebase runs at a quarter of the bus clock, 243 MHz on Wii. Both
+    // conversions below go through the reduced fraction 4000 / 243 ns per
+    // tick so long intervals don't accumulate rounding error.
+    const TB_NSEC_NUMER: u64 = 4000;
+    const TB_NSEC_DENOM: u64 = 243;
+
+    #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
     pub struct Instant {
-        t: Timespec,
+        t: u64,
     }
 
     #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -257,49 +263,46 @@ mod inner {
 
     pub const UNIX_EPOCH: SystemTime = SystemTime { t: Timespec::zero() };
 
-    pub const CLOCK_REALTIME: i32 = 1;
-    pub const CLOCK_MONOTONIC: i32 = 4;
+    // Seconds from UNIX_EPOCH to 2000-01-01 00:00:00, the epoch the GameCube
+    // and Wii real-time clock counts from.
+    const GC_EPOCH_OFFSET: u64 = 946_684_800;
 
     impl Instant {
         pub fn now() -> Instant {
-            Instant { t: (now(CLOCK_MONOTONIC as u32)) }
+            Instant { t: timebase() }
         }
 
         pub const fn zero() -> Instant {
-            Instant { t: Timespec::zero() }
+            Instant { t: 0 }
         }
 
         pub fn actually_monotonic() -> bool {
-            (cfg!(target_os = "linux") && cfg!(target_arch = "x86_64"))
-                || (cfg!(target_os = "linux") && cfg!(target_arch = "x86"))
-                || false // last clause, used so `||` is always trailing above
+            true
         }
 
         pub fn checked_sub_instant(&self, other: &Instant) -> Option<Duration> {
-            self.t.sub_timespec(&other.t).ok()
+            let diff = self.t.checked_sub(other.t)?;
+            let nanos = ticks2nanos(diff)?;
+            Some(Duration::new(nanos / NSEC_PER_SEC, (nanos % NSEC_PER_SEC) as u32))
         }
 
         pub fn checked_add_duration(&self, other: &Duration) -> Option<Instant> {
-            Some(Instant { t: self.t.checked_add_duration(other)? })
+            Some(Instant { t: self.t.checked_add(checked_dur2ticks(other)?)? })
         }
 
         pub fn checked_sub_duration(&self, other: &Duration) -> Option<Instant> {
-            Some(Instant { t: self.t.checked_sub_duration(other)? })
-        }
-    }
-
-    impl fmt::Debug for Instant {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            f.debug_struct("Instant")
-                .field("tv_sec", &self.t.t.tv_sec)
-                .field("tv_nsec", &self.t.t.tv_nsec)
-                .finish()
+            Some(Instant { t: self.t.checked_sub(checked_dur2ticks(other)?)? })
         }
     }
 
     impl SystemTime {
         pub fn now() -> SystemTime {
-            SystemTime { t: (now(CLOCK_REALTIME as u32)) }
+            let secs = GC_EPOCH_OFFSET + rtc_seconds() as u64;
+            SystemTime::from(ogc_sys::timespec {
+                tv_sec: secs as _,
+                tv_nsec: 0,
+                __bindgen_padding_0: 0,
+            })
         }
 
         pub fn sub_time(&self, other: &SystemTime) -> Result<Duration, Duration> {
@@ -330,10 +333,124 @@ mod inner {
         }
     }
 
-    fn now(clock: ogc_sys::clock_t) -> Timespec {
-        let mut t =
-            Timespec { t: ogc_sys::timespec { tv_sec: 0, tv_nsec: 0, __bindgen_padding_0: 0 } };
-        cvt(unsafe { ogc_sys::clock_gettime(clock as u32, &mut t.t) }).unwrap();
-        t
+    fn checked_dur2ticks(dur: &Duration) -> Option<u64> {
+        let nanos =
+            dur.as_secs().checked_mul(NSEC_PER_SEC)?.checked_add(dur.subsec_nanos() as u64)?;
+        Some(nanos2ticks(nanos))
+    }
+
+    // Rounds down, and fails for intervals beyond some 580 years.
+    fn ticks2nanos(ticks: u64) -> Option<u64> {
+        let q = ticks / TB_NSEC_DENOM;
+        let r = ticks % TB_NSEC_DENOM;
+        q.checked_mul(TB_NSEC_NUMER)?.checked_add(r * TB_NSEC_NUMER / TB_NSEC_DENOM)
+    }
+
+    // Rounds up, which makes it the exact inverse of `ticks2nanos`: a
+    // duration measured between two instants added back to the first one
+    // lands on the second one, however short or long it is.
+    fn nanos2ticks(nanos: u64) -> u64 {
+        let q = nanos / TB_NSEC_NUMER;
+        let r = nanos % TB_NSEC_NUMER;
+        q * TB_NSEC_DENOM + (r * TB_NSEC_DENOM + TB_NSEC_NUMER - 1) / TB_NSEC_NUMER
+    }
+
+    // Reads the 64-bit timebase. The upper half is read on both sides of the
+    // lower one so a carry out of TBL between the two reads is caught.
+    fn timebase() -> u64 {
+        loop {
+            let (hi, lo, hi2): (u32, u32, u32);
+            unsafe {
+                asm!("mftbu $0\n\tmftb $1\n\tmftbu $2"
+                     : "=r"(hi), "=r"(lo), "=r"(hi2)
+                     :
+                     :
+                     : "volatile");
+            }
+            if hi == hi2 {
+                return (hi as u64) << 32 | lo as u64;
+            }
+        }
+    }
+
+    // Attempts at reading the EXI clock before giving up on it. Reads only
+    // fail when another EXI transfer gets in the way.
+    const RTC_RETRIES: usize = 4;
+
+    // The last value read from the EXI clock, and the timebase at the time.
+    static mut LAST_RTC: Option<(u32, u64)> = None;
+
+    // Seconds since the GameCube epoch. The EXI clock only counts from
+    // whenever it was last reset; the system menu keeps the difference to the
+    // time the user set as the counter bias in SYSCONF. Like the console
+    // itself, the result is local time, there's no time zone to correct for.
+    fn rtc_seconds() -> u32 {
+        let mut rtc = 0;
+        let mut bias = 0;
+        unsafe {
+            if (0..RTC_RETRIES).any(|_| ogc_sys::__SYS_GetRTC(&mut rtc) != 0) {
+                let now = timebase();
+                let level = ogc_sys::IRQ_Disable();
+                LAST_RTC = Some((rtc, now));
+                ogc_sys::IRQ_Restore(level);
+            } else {
+                // Carry on from the last good read by the time passed since,
+                // so the clock doesn't jump. Without one, counting from boot
+                // at least keeps it moving forward.
+                let level = ogc_sys::IRQ_Disable();
+                let (last, since) = LAST_RTC.unwrap_or((0, 0));
+                ogc_sys::IRQ_Restore(level);
+                let elapsed = ticks2nanos(timebase() - since).unwrap_or(u64::max_value());
+                rtc = last.wrapping_add((elapsed / NSEC_PER_SEC) as u32);
+            }
+            // libogc's own `time` ignores an unreadable bias the same way.
+            if ogc_sys::CONF_GetCounterBias(&mut bias) < 0 {
+                bias = 0;
+            }
+        }
+        rtc.wrapping_add(bias)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const TICKS_PER_SEC: u64 = 60_750_000;
+
+        #[test]
+        fn ticks_round_trip() {
+            for &ticks in &[1, 2, TICKS_PER_SEC - 1, TICKS_PER_SEC, 5 * 3600 * TICKS_PER_SEC + 7] {
+                let start = Instant { t: 1234 };
+                let end = Instant { t: 1234 + ticks };
+                let dur = end.checked_sub_instant(&start).unwrap();
+                assert_eq!(start.checked_add_duration(&dur), Some(end));
+                assert_eq!(end.checked_sub_duration(&dur), Some(start));
+            }
+        }
+
+        #[test]
+        fn nanos_round_trip() {
+            let hours = 5 * 3600 * NSEC_PER_SEC;
+            for &nanos in &[NSEC_PER_SEC, 60 * NSEC_PER_SEC, hours] {
+                assert_eq!(ticks2nanos(nanos2ticks(nanos)), Some(nanos));
+            }
+            assert_eq!(nanos2ticks(NSEC_PER_SEC), TICKS_PER_SEC);
+            assert_eq!(nanos2ticks(hours), 5 * 3600 * TICKS_PER_SEC);
+            // A single tick is 16.46 ns.
+            assert_eq!(ticks2nanos(1), Some(16));
+            assert_eq!(nanos2ticks(16), 1);
+            assert_eq!(nanos2ticks(17), 2);
+        }
+
+        #[test]
+        fn overflow() {
+            let max = Instant { t: u64::max_value() };
+            let zero = Instant::zero();
+            assert_eq!(max.checked_sub_instant(&zero), None);
+            assert_eq!(zero.checked_sub_instant(&Instant { t: 1 }), None);
+            assert_eq!(max.checked_add_duration(&Duration::from_secs(1)), None);
+            assert_eq!(zero.checked_sub_duration(&Duration::from_nanos(1)), None);
+            assert_eq!(checked_dur2ticks(&Duration::from_secs(u64::max_value())), None);
+        }
     }
 }
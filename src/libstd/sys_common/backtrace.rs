@@ -4,13 +4,10 @@ use crate::env;
 use crate::io;
 use crate::io::prelude::*;
 use crate::mem;
-use crate::path::{self, Path};
-use crate::ptr;
 use crate::sync::atomic::{self, Ordering};
+use crate::sys::backtrace;
 use crate::sys::mutex::Mutex;
 
-use backtrace::{BytesOrWideString, Frame, Symbol};
-
 pub const HEX_WIDTH: usize = 2 + 2 * mem::size_of::<usize>();
 
 /// Max number of frames to print.
@@ -20,16 +17,13 @@ const MAX_NB_FRAMES: usize = 100;
 pub fn print(w: &mut dyn Write, format: PrintFormat) -> io::Result<()> {
     static LOCK: Mutex = Mutex::new();
 
-    // There are issues currently linking libbacktrace into tests, and in
-    // general during libstd's own unit tests we're not testing this path. In
-    // test mode immediately return here to optimize away any references to the
-    // libbacktrace symbols
+    // During libstd's own unit tests we're not testing this path, and they
+    // don't run on the console the stack walk is written for.
     if cfg!(test) {
         return Ok(());
     }
 
     // Use a lock to prevent mixed output in multithreading context.
-    // Some platforms also requires it, like `SymFromAddr` on Windows.
     unsafe {
         LOCK.lock();
         let res = _print(w, format);
@@ -41,21 +35,38 @@ pub fn print(w: &mut dyn Write, format: PrintFormat) -> io::Result<()> {
 fn _print(w: &mut dyn Write, format: PrintFormat) -> io::Result<()> {
     writeln!(w, "stack backtrace:")?;
 
-    let mut printer = Printer::new(format, w);
-    unsafe {
-        backtrace::trace_unsynchronized(|frame| {
-            let mut hit = false;
-            backtrace::resolve_frame_unsynchronized(frame, |symbol| {
-                hit = true;
-                printer.output(frame, Some(symbol));
-            });
-            if !hit {
-                printer.output(frame, None);
-            }
-            !printer.done
-        });
+    // Frames are printed as they are walked, without allocating, since the
+    // heap may well be what's broken. The first `MAX_NB_FRAMES` are kept for
+    // the symbolization note.
+    let mut frames = [0; MAX_NB_FRAMES];
+    let mut idx = 0;
+    let mut skipped = false;
+    let mut res = Ok(());
+    backtrace::trace(|ip| {
+        if idx == MAX_NB_FRAMES && format == PrintFormat::Short {
+            skipped = true;
+            return false;
+        }
+        if let Some(frame) = frames.get_mut(idx) {
+            *frame = ip;
+        }
+        res = writeln!(w, "  {:2}: {:#02$x}", idx, ip, HEX_WIDTH);
+        idx += 1;
+        res.is_ok()
+    });
+    res?;
+
+    let frames = &frames[..idx.min(MAX_NB_FRAMES)];
+    if !frames.is_empty() {
+        // There are no symbols on the console, so leave resolving the
+        // addresses to the toolchain.
+        write!(w, "note: symbolize with `powerpc-eabi-addr2line -f -C -i -e <program>.elf")?;
+        for ip in frames {
+            write!(w, " {:#x}", ip)?;
+        }
+        writeln!(w, "`")?;
     }
-    if printer.skipped {
+    if skipped {
         writeln!(
             w,
             "note: Some details are omitted, \
@@ -114,129 +125,3 @@ pub fn log_enabled() -> Option<PrintFormat> {
     );
     val
 }
-
-struct Printer<'a, 'b> {
-    format: PrintFormat,
-    done: bool,
-    skipped: bool,
-    idx: usize,
-    out: &'a mut (dyn Write + 'b),
-}
-
-impl<'a, 'b> Printer<'a, 'b> {
-    fn new(format: PrintFormat, out: &'a mut (dyn Write + 'b)) -> Printer<'a, 'b> {
-        Printer { format, done: false, skipped: false, idx: 0, out }
-    }
-
-    /// Prints the symbol of the backtrace frame.
-    ///
-    /// These output functions should now be used everywhere to ensure consistency.
-    /// You may want to also use `output_fileline`.
-    fn output(&mut self, frame: &Frame, symbol: Option<&Symbol>) {
-        if self.idx > MAX_NB_FRAMES {
-            self.done = true;
-            self.skipped = true;
-            return;
-        }
-        if self._output(frame, symbol).is_err() {
-            self.done = true;
-        }
-        self.idx += 1;
-    }
-
-    fn _output(&mut self, frame: &Frame, symbol: Option<&Symbol>) -> io::Result<()> {
-        if self.format == PrintFormat::Short {
-            if let Some(sym) = symbol.and_then(|s| s.name()).and_then(|s| s.as_str()) {
-                if sym.contains("__rust_begin_short_backtrace") {
-                    self.skipped = true;
-                    self.done = true;
-                    return Ok(());
-                }
-            }
-
-            // Remove the `17: 0x0 - <unknown>` line.
-            if self.format == PrintFormat::Short && frame.ip() == ptr::null_mut() {
-                self.skipped = true;
-                return Ok(());
-            }
-        }
-
-        match self.format {
-            PrintFormat::Full => {
-                write!(self.out, "  {:2}: {:2$?} - ", self.idx, frame.ip(), HEX_WIDTH)?
-            }
-            PrintFormat::Short => write!(self.out, "  {:2}: ", self.idx)?,
-        }
-
-        match symbol.and_then(|s| s.name()) {
-            Some(symbol) => {
-                match self.format {
-                    PrintFormat::Full => write!(self.out, "{}", symbol)?,
-                    // strip the trailing hash if short mode
-                    PrintFormat::Short => write!(self.out, "{:#}", symbol)?,
-                }
-            }
-            None => self.out.write_all(b"<unknown>")?,
-        }
-        self.out.write_all(b"\n")?;
-        if let Some(sym) = symbol {
-            self.output_fileline(sym)?;
-        }
-        Ok(())
-    }
-
-    /// Prints the filename and line number of the backtrace frame.
-    ///
-    /// See also `output`.
-    fn output_fileline(&mut self, symbol: &Symbol) -> io::Result<()> {
-        #[cfg(windows)]
-        let path_buf;
-        let file = match symbol.filename_raw() {
-            #[cfg(unix)]
-            Some(BytesOrWideString::Bytes(bytes)) => {
-                use crate::os::unix::prelude::*;
-                Path::new(crate::ffi::OsStr::from_bytes(bytes))
-            }
-            #[cfg(not(unix))]
-            Some(BytesOrWideString::Bytes(bytes)) => {
-                Path::new(crate::str::from_utf8(bytes).unwrap_or("<unknown>"))
-            }
-            #[cfg(windows)]
-            Some(BytesOrWideString::Wide(wide)) => {
-                use crate::os::windows::prelude::*;
-                path_buf = crate::ffi::OsString::from_wide(wide);
-                Path::new(&path_buf)
-            }
-            #[cfg(not(windows))]
-            Some(BytesOrWideString::Wide(_wide)) => Path::new("<unknown>"),
-            None => return Ok(()),
-        };
-        let line = match symbol.lineno() {
-            Some(line) => line,
-            None => return Ok(()),
-        };
-        // prior line: "  ##: {:2$} - func"
-        self.out.write_all(b"")?;
-        match self.format {
-            PrintFormat::Full => write!(self.out, "           {:1$}", "", HEX_WIDTH)?,
-            PrintFormat::Short => write!(self.out, "           ")?,
-        }
-
-        let mut already_printed = false;
-        if self.format == PrintFormat::Short && file.is_absolute() {
-            if let Ok(cwd) = env::current_dir() {
-                if let Ok(stripped) = file.strip_prefix(&cwd) {
-                    if let Some(s) = stripped.to_str() {
-                        write!(self.out, "  at .{}{}:{}", path::MAIN_SEPARATOR, s, line)?;
-                        already_printed = true;
-                    }
-                }
-            }
-        }
-        if !already_printed {
-            write!(self.out, "  at {}:{}", file.display(), line)?;
-        }
-
-        self.out.write_all(b"\n")
-    }
-}
@@ -59,15 +59,15 @@ pub mod mutex;
 pub mod os_str_bytes;
 pub mod poison;
 pub mod remutex;
-//pub mod rwlock;
+pub mod rwlock;
 pub mod thread;
 pub mod thread_info;
 pub mod thread_local;
 pub mod util;
 //pub mod wtf8;
 pub mod bytestring;
-//pub mod process;
-//pub mod fs;
+pub mod process;
+pub mod fs;
 pub mod net;
 
 // common error constructors
@@ -1,14 +1,13 @@
 use crate::cmp;
-use crate::ffi::CString;
 use crate::fmt;
 use crate::io::{self, Error, ErrorKind, IoSlice, IoSliceMut};
 use crate::mem;
-use crate::net::{SocketAddr, Shutdown, Ipv4Addr, Ipv6Addr};
-use crate::ptr;
-use crate::sys::net::{cvt, cvt_r, cvt_gai, Socket, init, wrlen_t};
+use crate::net::{SocketAddr, SocketAddrV4, Shutdown, Ipv4Addr, Ipv6Addr};
+use crate::sys::net::{cvt_net, cvt_net_r, Socket, init, wrlen_t};
 use crate::sys::net::netc as c;
 use crate::sys_common::{AsInner, FromInner, IntoInner};
 use crate::time::Duration;
+use crate::vec;
 use crate::convert::{TryFrom, TryInto};
 
 use libc::{c_int, c_void};
@@ -49,12 +48,14 @@ const MSG_NOSIGNAL: c_int = 0x0;
 // sockaddr and misc bindings
 ////////////////////////////////////////////////////////////////////////////////
 
+// Sockets live in the IOS network stack, so their options have to go through
+// libogc rather than newlib.
 pub fn setsockopt<T>(sock: &Socket, opt: c_int, val: c_int,
                      payload: T) -> io::Result<()> {
     unsafe {
         let payload = &payload as *const T as *const c_void;
-        cvt(c::setsockopt(*sock.as_inner(), opt, val, payload,
-                          mem::size_of::<T>() as c::socklen_t))?;
+        cvt_net(ogc_sys::net_setsockopt(*sock.as_inner(), opt as u32, val as u32, payload,
+                                        mem::size_of::<T>() as c::socklen_t))?;
         Ok(())
     }
 }
@@ -64,21 +65,21 @@ pub fn getsockopt<T: Copy>(sock: &Socket, opt: c_int,
     unsafe {
         let mut slot: T = mem::zeroed();
         let mut len = mem::size_of::<T>() as c::socklen_t;
-        cvt(c::getsockopt(*sock.as_inner(), opt, val,
-                          &mut slot as *mut _ as *mut _,
-                          &mut len))?;
+        cvt_net(ogc_sys::net_getsockopt(*sock.as_inner(), opt as u32, val as u32,
+                                        &mut slot as *mut _ as *mut _,
+                                        &mut len))?;
         assert_eq!(len as usize, mem::size_of::<T>());
         Ok(slot)
     }
 }
 
 fn sockname<F>(f: F) -> io::Result<SocketAddr>
-    where F: FnOnce(*mut c::sockaddr, *mut c::socklen_t) -> c_int
+    where F: FnOnce(*mut ogc_sys::sockaddr, *mut ogc_sys::socklen_t) -> i32
 {
     unsafe {
         let mut storage: c::sockaddr_storage = mem::zeroed();
-        let mut len = mem::size_of_val(&storage) as c::socklen_t;
-        cvt(f(&mut storage as *mut _ as *mut _, &mut len))?;
+        let mut len = mem::size_of_val(&storage) as ogc_sys::socklen_t;
+        cvt_net(f(&mut storage as *mut _ as *mut _, &mut len))?;
         sockaddr_to_addr(&storage, len as usize)
     }
 }
@@ -119,8 +120,7 @@ fn to_ipv6mr_interface(value: u32) -> libc::c_uint {
 ////////////////////////////////////////////////////////////////////////////////
 
 pub struct LookupHost {
-    original: *mut c::addrinfo,
-    cur: *mut c::addrinfo,
+    addrs: vec::IntoIter<Ipv4Addr>,
     port: u16
 }
 
@@ -133,27 +133,8 @@ impl LookupHost {
 impl Iterator for LookupHost {
     type Item = SocketAddr;
     fn next(&mut self) -> Option<SocketAddr> {
-        loop {
-            unsafe {
-                let cur = self.cur.as_ref()?;
-                self.cur = cur.ai_next;
-                match sockaddr_to_addr(mem::transmute(cur.ai_addr),
-                                       cur.ai_addrlen as usize)
-                {
-                    Ok(addr) => return Some(addr),
-                    Err(_) => continue,
-                }
-            }
-        }
-    }
-}
-
-unsafe impl Sync for LookupHost {}
-unsafe impl Send for LookupHost {}
-
-impl Drop for LookupHost {
-    fn drop(&mut self) {
-        unsafe { c::freeaddrinfo(self.original) }
+        let ip = self.addrs.next()?;
+        Some(SocketAddr::V4(SocketAddrV4::new(ip, self.port)))
     }
 }
 
@@ -187,15 +168,8 @@ impl<'a> TryFrom<(&'a str, u16)> for LookupHost {
     fn try_from((host, port): (&'a str, u16)) -> io::Result<LookupHost> {
         init();
 
-        let c_host = CString::new(host)?;
-        let mut hints: c::addrinfo = unsafe { mem::zeroed() };
-        hints.ai_socktype = c::SOCK_STREAM;
-        let mut res = ptr::null_mut();
-        unsafe {
-            cvt_gai(c::getaddrinfo(c_host.as_ptr(), ptr::null(), &hints, &mut res)).map(|_| {
-                LookupHost { original: res, cur: res, port }
-            })
-        }
+        let addrs = crate::sys::net::lookup_host(host)?;
+        Ok(LookupHost { addrs: addrs.into_iter(), port })
     }
 }
 
@@ -216,7 +190,7 @@ impl TcpStream {
         let sock = Socket::new(addr, c::SOCK_STREAM)?;
 
         let (addrp, len) = addr.into_inner();
-        cvt_r(|| unsafe { c::connect(*sock.as_inner(), addrp, len) })?;
+        cvt_net_r(|| unsafe { ogc_sys::net_connect(*sock.as_inner(), addrp as *mut _, len) })?;
         Ok(TcpStream { inner: sock })
     }
 
@@ -262,11 +236,11 @@ impl TcpStream {
 
     pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
         let len = cmp::min(buf.len(), <wrlen_t>::max_value() as usize) as wrlen_t;
-        let ret = cvt(unsafe {
-            c::send(*self.inner.as_inner(),
-                    buf.as_ptr() as *const c_void,
-                    len,
-                    MSG_NOSIGNAL)
+        let ret = cvt_net(unsafe {
+            ogc_sys::net_send(*self.inner.as_inner(),
+                              buf.as_ptr() as *const c_void,
+                              len,
+                              MSG_NOSIGNAL as u32)
         })?;
         Ok(ret as usize)
     }
@@ -277,13 +251,13 @@ impl TcpStream {
 
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
         sockname(|buf, len| unsafe {
-            c::getpeername(*self.inner.as_inner(), buf, len)
+            ogc_sys::net_getpeername(*self.inner.as_inner(), buf, len)
         })
     }
 
     pub fn socket_addr(&self) -> io::Result<SocketAddr> {
         sockname(|buf, len| unsafe {
-            c::getsockname(*self.inner.as_inner(), buf, len)
+            ogc_sys::net_getsockname(*self.inner.as_inner(), buf, len)
         })
     }
 
@@ -371,10 +345,10 @@ impl TcpListener {
 
         // Bind our new socket
         let (addrp, len) = addr.into_inner();
-        cvt(unsafe { c::bind(*sock.as_inner(), addrp, len as _) })?;
+        cvt_net(unsafe { ogc_sys::net_bind(*sock.as_inner(), addrp as *mut _, len as _) })?;
 
         // Start listening
-        cvt(unsafe { c::listen(*sock.as_inner(), 128) })?;
+        cvt_net(unsafe { ogc_sys::net_listen(*sock.as_inner(), 128) })?;
         Ok(TcpListener { inner: sock })
     }
 
@@ -384,7 +358,7 @@ impl TcpListener {
 
     pub fn socket_addr(&self) -> io::Result<SocketAddr> {
         sockname(|buf, len| unsafe {
-            c::getsockname(*self.inner.as_inner(), buf, len)
+            ogc_sys::net_getsockname(*self.inner.as_inner(), buf, len)
         })
     }
 
@@ -464,7 +438,7 @@ impl UdpSocket {
 
         let sock = Socket::new(addr, c::SOCK_DGRAM)?;
         let (addrp, len) = addr.into_inner();
-        cvt(unsafe { c::bind(*sock.as_inner(), addrp, len as _) })?;
+        cvt_net(unsafe { ogc_sys::net_bind(*sock.as_inner(), addrp as *mut _, len as _) })?;
         Ok(UdpSocket { inner: sock })
     }
 
@@ -474,13 +448,13 @@ impl UdpSocket {
 
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
         sockname(|buf, len| unsafe {
-            c::getpeername(*self.inner.as_inner(), buf, len)
+            ogc_sys::net_getpeername(*self.inner.as_inner(), buf, len)
         })
     }
 
     pub fn socket_addr(&self) -> io::Result<SocketAddr> {
         sockname(|buf, len| unsafe {
-            c::getsockname(*self.inner.as_inner(), buf, len)
+            ogc_sys::net_getsockname(*self.inner.as_inner(), buf, len)
         })
     }
 
@@ -495,10 +469,10 @@ impl UdpSocket {
     pub fn send_to(&self, buf: &[u8], dst: &SocketAddr) -> io::Result<usize> {
         let len = cmp::min(buf.len(), <wrlen_t>::max_value() as usize) as wrlen_t;
         let (dstp, dstlen) = dst.into_inner();
-        let ret = cvt(unsafe {
-            c::sendto(*self.inner.as_inner(),
-                      buf.as_ptr() as *const c_void, len,
-                      MSG_NOSIGNAL, dstp, dstlen)
+        let ret = cvt_net(unsafe {
+            ogc_sys::net_sendto(*self.inner.as_inner(),
+                                buf.as_ptr() as *const c_void, len,
+                                MSG_NOSIGNAL as u32, dstp as *mut _, dstlen)
         })?;
         Ok(ret as usize)
     }
@@ -622,18 +596,20 @@ impl UdpSocket {
 
     pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
         let len = cmp::min(buf.len(), <wrlen_t>::max_value() as usize) as wrlen_t;
-        let ret = cvt(unsafe {
-            c::send(*self.inner.as_inner(),
-                    buf.as_ptr() as *const c_void,
-                    len,
-                    MSG_NOSIGNAL)
+        let ret = cvt_net(unsafe {
+            ogc_sys::net_send(*self.inner.as_inner(),
+                              buf.as_ptr() as *const c_void,
+                              len,
+                              MSG_NOSIGNAL as u32)
         })?;
         Ok(ret as usize)
     }
 
     pub fn connect(&self, addr: io::Result<&SocketAddr>) -> io::Result<()> {
         let (addrp, len) = addr?.into_inner();
-        cvt_r(|| unsafe { c::connect(*self.inner.as_inner(), addrp, len) }).map(|_| ())
+        cvt_net_r(|| unsafe {
+            ogc_sys::net_connect(*self.inner.as_inner(), addrp as *mut _, len)
+        }).map(|_| ())
     }
 }
 
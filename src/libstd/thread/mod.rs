@@ -260,6 +260,8 @@ pub struct Builder {
     name: Option<String>,
     // The size of the stack for the spawned thread in bytes
     stack_size: Option<usize>,
+    // The LWP priority of the spawned thread, see `os::rvl_ios::thread`
+    priority: Option<u8>,
 }
 
 impl Builder {
@@ -283,7 +285,7 @@ impl Builder {
     /// ```
     #[stable(feature = "rust1", since = "1.0.0")]
     pub fn new() -> Builder {
-        Builder { name: None, stack_size: None }
+        Builder { name: None, stack_size: None, priority: None }
     }
 
     /// Names the thread-to-be. Currently the name is used for identification
@@ -339,6 +341,12 @@ impl Builder {
         self
     }
 
+    // Backs `os::rvl_ios::thread::BuilderExt::priority`.
+    pub(crate) fn set_priority(mut self, priority: u8) -> Builder {
+        self.priority = Some(priority);
+        self
+    }
+
     /// Spawns a new thread by taking ownership of the `Builder`, and returns an
     /// [`io::Result`] to its [`JoinHandle`].
     ///
@@ -455,9 +463,10 @@ impl Builder {
         F: Send + 'a,
         T: Send + 'a,
     {
-        let Builder { name, stack_size } = self;
+        let Builder { name, stack_size, priority } = self;
 
         let stack_size = stack_size.unwrap_or_else(thread::min_stack);
+        let priority = priority.unwrap_or(imp::DEFAULT_PRIORITY);
 
         let my_thread = Thread::new(name);
         let their_thread = my_thread.clone();
@@ -494,6 +503,7 @@ impl Builder {
             // returning.
             native: Some(imp::Thread::new(
                 stack_size,
+                priority,
                 mem::transmute::<Box<dyn FnOnce() + 'a>, Box<dyn FnOnce() + 'static>>(Box::new(
                     main,
                 )),
@@ -684,8 +694,7 @@ pub fn current() -> Thread {
 /// [`Condvar`]: ../../std/sync/struct.Condvar.html
 #[stable(feature = "rust1", since = "1.0.0")]
 pub fn yield_now() {
-    unimplemented!()
-    //imp::Thread::yield_now()
+    imp::Thread::yield_now()
 }
 
 /// Determines whether the current thread is unwinding because of panic.
@@ -759,8 +768,7 @@ pub fn panicking() -> bool {
 #[stable(feature = "rust1", since = "1.0.0")]
 #[rustc_deprecated(since = "1.6.0", reason = "replaced by `std::thread::sleep`")]
 pub fn sleep_ms(ms: u32) {
-    unimplemented!()
-    //sleep(Duration::from_millis(ms as u64))
+    sleep(Duration::from_millis(ms as u64))
 }
 
 /// Puts the current thread to sleep for at least the specified amount of time.
@@ -791,8 +799,7 @@ pub fn sleep_ms(ms: u32) {
 /// ```
 #[stable(feature = "thread_sleep", since = "1.4.0")]
 pub fn sleep(dur: Duration) {
-    unimplemented!()
-    //imp::Thread::sleep(dur)
+    imp::Thread::sleep(dur)
 }
 
 // constants for park/unpark
@@ -948,8 +955,7 @@ pub fn park() {
 #[stable(feature = "rust1", since = "1.0.0")]
 #[rustc_deprecated(since = "1.6.0", reason = "replaced by `std::thread::park_timeout`")]
 pub fn park_timeout_ms(ms: u32) {
-    unimplemented!()
-    //park_timeout(Duration::from_millis(ms as u64))
+    park_timeout(Duration::from_millis(ms as u64))
 }
 
 /// Blocks unless or until the current thread's token is made available or
@@ -38,6 +38,9 @@ fn main() {
         println!("cargo:rustc-link-lib=gcc");
     } else if target.contains("cloudabi") {
         println!("cargo:rustc-link-lib=unwind");
+    } else if target.contains("rvl-ios") {
+        // devkitPPC's libgcc_eh; the EH frames are registered by rsbegin.o.
+        println!("cargo:rustc-link-lib=static-nobundle=gcc_eh");
     }
 }
 
@@ -33,7 +33,8 @@ pub unsafe fn drop_in_place<T: ?Sized>(to_drop: *mut T) {
     drop_in_place(to_drop);
 }
 
-#[cfg(all(target_os = "windows", target_arch = "x86", target_env = "gnu"))]
+#[cfg(any(all(target_os = "windows", target_arch = "x86", target_env = "gnu"),
+          target_os = "rvl-ios"))]
 pub mod eh_frames {
     #[no_mangle]
     #[link_section = ".eh_frame"]
@@ -66,7 +67,7 @@ pub mod eh_frames {
         fn rust_eh_unregister_frames(eh_frame_begin: *const u8, object: *mut u8);
     }
 
-    unsafe fn init() {
+    unsafe extern "C" fn init() {
         // register unwind info on module startup
         rust_eh_register_frames(
             &__EH_FRAME_BEGIN__ as *const u8,
@@ -74,7 +75,7 @@ pub mod eh_frames {
         );
     }
 
-    unsafe fn uninit() {
+    unsafe extern "C" fn uninit() {
         // unregister on shutdown
         rust_eh_unregister_frames(
             &__EH_FRAME_BEGIN__ as *const u8,
@@ -83,6 +84,7 @@ pub mod eh_frames {
     }
 
     // MSVC-specific init/uninit routine registration
+    #[cfg(target_os = "windows")]
     pub mod ms_init {
         // .CRT$X?? sections are roughly analogous to ELF's .init_array and .fini_array,
         // except that they exploit the fact that linker will sort them alphabitically,
@@ -94,9 +96,22 @@ pub mod eh_frames {
         // we place our initialization callback into .CRT$XIB.
 
         #[link_section = ".CRT$XIB"] // .CRT$XI? : C initialization callbacks
-        pub static P_INIT: unsafe fn() = super::init;
+        pub static P_INIT: unsafe extern "C" fn() = super::init;
 
         #[link_section = ".CRT$XTY"] // .CRT$XT? : C termination callbacks
-        pub static P_UNINIT: unsafe fn() = super::uninit;
+        pub static P_UNINIT: unsafe extern "C" fn() = super::uninit;
+    }
+
+    // ELF init/fini routine registration, run by newlib's
+    // `__libc_init_array`/`__libc_fini_array` around `main`.
+    #[cfg(target_os = "rvl-ios")]
+    pub mod elf_init {
+        #[used]
+        #[link_section = ".init_array"]
+        pub static P_INIT: unsafe extern "C" fn() = super::init;
+
+        #[used]
+        #[link_section = ".fini_array"]
+        pub static P_UNINIT: unsafe extern "C" fn() = super::uninit;
     }
 }